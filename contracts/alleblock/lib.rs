@@ -5,27 +5,116 @@ use ink_lang as ink;
 #[ink::contract]
 mod alleblock {
     use ink_prelude::{string::String, vec::Vec};
-    use ink_storage::traits::{PackedLayout, SpreadLayout};
+    use ink_storage::traits::{PackedLayout, SpreadLayout, SpreadAllocate};
+    use ink_primitives::Key;
+    use ink_storage::Mapping;
+    use ink_env::hash::Blake2x256;
 
-    #[derive(PackedLayout,SpreadLayout, Debug, PartialEq, Eq, scale::Encode, scale::Decode, Clone)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum AuctionState {
-        InProgress,
-        Finished,
-        Cancelled
-    }
+    // auction rules, kept in a crate which can be tested without ink!
+    pub use alleblock_core::{AuctionKind, AuctionState, BidIncrement, BuyNow, DurationBounds, FeeQuote, FeeTerms, SealedPricing, SoftClose, UnrevealedDeposit};
+    use alleblock_core::{CoreError, FinishedAuction, Lead, DEFAULT_DURATION_BOUNDS, MAX_FEE_BPS};
+
+    // needed to call psp22, psp34 and nft storage contracts
+    use ink_env::call::FromAccountId;
+    use ink_lang::codegen::TraitCallBuilder;
+    use ink_lang::codegen::EmitEvent;
+    use ink_lang::reflect::ContractEventBase;
+    use nft_storage::NftStorageRef;
+    pub use openbrush::contracts::traits::psp34::Id;
+    use openbrush::contracts::traits::psp34::PSP34Error;
+    use openbrush::contracts::traits::psp34::PSP34Ref;
+    use openbrush::contracts::traits::psp22::PSP22Error;
+    use openbrush::contracts::traits::psp22::PSP22Ref;
 
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
-        TooLowBidError,
+        /// carries the lowest amount which would be accepted
+        TooLowBidError(u128),
         TooLowFeeError,
         AfterFinishDateError,
         BeforeFinishDateError,
         AuctionNotInProgressError,
         NoSuchAuctionError,
         NotAnOwnerError,
-        TransferError
+        TransferError,
+        NoNftAllowanceError,
+        NftTransferError,
+        NothingToWithdrawError,
+        WrongAuctionKindError,
+        InvalidPriceError,
+        NotCommitPhaseError,
+        NotRevealPhaseError,
+        NoSuchBidError,
+        InvalidRevealError,
+        TooManyBidsError,
+        BuyNowUnavailableError,
+        WrongCurrencyError,
+        /// PSP22 token refused to transfer
+        TokenTransferError(PSP22Error),
+        InvalidFeeError,
+        NotProposedOwnerError,
+        PausedError,
+        NotPausedError,
+        UpgradeError,
+        ReentrancyError,
+        /// nft storage couldn't be called or failed, see CallFailure
+        NftStorageCallError(CallFailure),
+        /// answer of nft storage couldn't be decoded
+        NftStorageDecodeError,
+        /// nft storage refused to transfer the nft
+        NftStorageTransferError(PSP34Error),
+        InvalidDurationError,
+    }
+
+    impl From<CoreError> for Error {
+        fn from(error: CoreError) -> Self {
+            match error {
+                CoreError::TooLowBid(min_bid) => Error::TooLowBidError(min_bid),
+                CoreError::BuyNowUnavailable => Error::BuyNowUnavailableError,
+                CoreError::WrongAuctionKind => Error::WrongAuctionKindError,
+                CoreError::InvalidDuration => Error::InvalidDurationError,
+            }
+        }
+    }
+
+    /// why a call to another contract failed
+    /// CalleeTrapped -- called contract panicked
+    /// CalleeReverted -- called contract returned an error, which reverted its changes
+    /// CodeNotFound -- called contract's code is missing
+    /// NotCallable -- called account isn't a contract
+    /// Unknown -- any other failure
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum CallFailure {
+        CalleeTrapped,
+        CalleeReverted,
+        CodeNotFound,
+        NotCallable,
+        Unknown,
+    }
+
+    impl From<ink_env::Error> for CallFailure {
+        fn from(error: ink_env::Error) -> Self {
+            match error {
+                ink_env::Error::CalleeTrapped => CallFailure::CalleeTrapped,
+                ink_env::Error::CalleeReverted => CallFailure::CalleeReverted,
+                ink_env::Error::CodeNotFound => CallFailure::CodeNotFound,
+                ink_env::Error::NotCallable => CallFailure::NotCallable,
+                _ => CallFailure::Unknown,
+            }
+        }
+    }
+
+    /// bid committed to a sealed-bid auction
+    /// hash -- blake2x256 of scale encoded (auction_id, bidder, amount, salt)
+    /// deposit -- money locked with the commitment, has to cover the revealed amount
+    #[derive(PackedLayout, PartialEq, Eq, SpreadLayout, scale::Encode, scale::Decode, Clone, Debug)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct SealedBid {
+        pub hash: Hash,
+        pub deposit: u128,
+        pub revealed: bool,
     }
 
     #[derive(PackedLayout, PartialEq, SpreadLayout, scale::Encode, scale::Decode, Clone, Debug)]
@@ -37,27 +126,293 @@ mod alleblock {
         pub starting_bid: u128,
         pub highest_bid: u128,
         pub highest_bidder: AccountId,
+        pub highest_bidder_max: u128,
         pub creation_date: Timestamp,
         pub finish_date: Timestamp,
-        pub auction_state: AuctionState
+        pub auction_state: AuctionState,
+        pub nft_contract_account: Option<AccountId>,
+        pub nft_token_id: Option<Id>,
+        pub soft_close: Option<SoftClose>,
+        pub extended_by: u64,
+        pub kind: AuctionKind,
+        pub second_highest_bid: u128,
+        pub buy_now: Option<BuyNow>,
+        pub min_increment: Option<BidIncrement>,
+        pub reserve_hash: Option<Hash>,
+        pub reserve: Option<u128>,
+        pub payment_token: Option<AccountId>,
+        pub fee_terms: FeeTerms,
+        pub pause_index: u32,
+    }
+
+    /// entry of the bid log of an auction
+    #[derive(PackedLayout, PartialEq, Eq, SpreadLayout, scale::Encode, scale::Decode, Clone, Debug)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct BidRecord {
+        pub bidder: AccountId,
+        pub amount: u128,
+        pub timestamp: Timestamp,
+    }
+
+    /// conditions of auctions returned by get_auctions_page, None matches every auction
+    /// highest_bidder -- matches only auctions with at least one bid
+    /// nft_contract -- contract of the auctioned nft
+    /// finish_date_passed -- whether the finish date is already in the past
+    #[derive(PartialEq, Eq, scale::Encode, scale::Decode, Clone, Debug, Default)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct AuctionFilter {
+        pub state: Option<AuctionState>,
+        pub owner: Option<AccountId>,
+        pub highest_bidder: Option<AccountId>,
+        pub nft_contract: Option<AccountId>,
+        pub finish_date_passed: Option<bool>,
+    }
+
+    /// page of auctions matching a filter
+    /// next_cursor -- id from which the next page is looked up, None if every auction was looked at
+    /// Note: matching auctions aren't counted, it would take a scan of every auction on every page
+    #[derive(PartialEq, scale::Encode, scale::Decode, Clone, Debug)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct AuctionsPage {
+        pub auctions: Vec<AuctionInfo>,
+        pub next_cursor: Option<u64>,
+    }
+
+    /// auction as saved in the storage, tagged with the storage version which saved it
+    /// when AuctionInfo gets new fields, the current layout stays here as a new variant
+    /// and load_auction turns it into the latest one, so auctions saved by older code keep working
+    #[derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode, Clone, Debug)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum VersionedAuctionInfo {
+        #[codec(index = 1)]
+        V1(AuctionInfo),
+    }
+
+    impl VersionedAuctionInfo {
+        /// auction in the latest layout
+        fn latest(self) -> AuctionInfo {
+            match self {
+                VersionedAuctionInfo::V1(auction) => auction,
+            }
+        }
     }
+
+    /// storage cell keeping the version of the storage layout, written by the constructor
+    /// kept outside of the contract struct, because reading a missing struct field fails before any message runs
+    const STORAGE_VERSION_KEY: [u8; 32] = [0xff; 32];
+
+    /// version of the storage layout used by this code, see migrate_storage
+    /// 1 -- auctions kept by id as VersionedAuctionInfo, first layout which can be upgraded with set_code
+    /// Note: code set by set_code loads the contract struct from storage written by older code,
+    /// so fields added to the struct after version 1 have to be Mappings (a missing plain field fails the load)
+    /// or kept in cells of their own, like the cells below
+    const STORAGE_VERSION: u32 = 1;
+
+    /// storage cell which is set while a message calling other contracts runs, see non_reentrant
+    /// kept outside of the contract struct, because the struct is written only after the message ends
+    const REENTRANCY_LOCK_KEY: [u8; 32] = [0xfe; 32];
+
+    /// storage cell keeping the duration bounds set by the contract owner, see set_duration_bounds
+    /// kept outside of the contract struct, so contracts upgraded from older code use the defaults
+    const DURATION_BOUNDS_KEY: [u8; 32] = [0xfd; 32];
+
+    /// maximal number of auctions returned by a single get_auctions_page call
+    const MAX_PAGE_SIZE: u64 = 100;
+
+    /// maximal number of auctions looked at by a single get_auctions_page call, matching or not
+    const MAX_PAGE_SCAN: u64 = 1_000;
+
+    /// maximal number of bids logged for a single auction, later bids are still accepted but not logged
+    const MAX_BID_LOG: u64 = 10_000;
+
+    /// maximal number of bidders committing to a single sealed-bid auction
+    /// keeps settlement, which goes through all the commitments, within the gas limit
+    const MAX_SEALED_BIDS: usize = 100;
     
 
     #[ink(storage)]
+    #[derive(SpreadAllocate)]
     pub struct Alleblock {
-        /// list of all contract's auctions
-        auctions: Vec<AuctionInfo>,
+        /// all contract's auctions, by id
+        auctions: Mapping<u64, VersionedAuctionInfo>,
 
         /// fee for creating the auction
         create_auction_fee: u128,
 
-        /// fraction of the final price that contract takes as fee
-        /// the contract will take <highest_bid>/<finalize_fee_interest> currency
-        finalize_fee_interest: u32,
+        /// fraction of the final price that contract takes as fee, in basis points
+        settlement_fee_bps: u32,
 
         /// account of the owner of this contract
         /// this account receives fees gathered by this contract
         contract_owner: AccountId,
+
+        /// address of friendly nft storage
+        nft_storage: AccountId,
+
+        /// money owed to accounts (refunds, payouts and fees)
+        /// every account collects its money by calling withdraw
+        pending_withdrawals: Mapping<AccountId, u128>,
+
+        /// bids committed to sealed-bid auctions, by auction id and bidder
+        sealed_bids: Mapping<(u64, AccountId), SealedBid>,
+
+        /// bidders who committed to given sealed-bid auction
+        sealed_bidders: Mapping<u64, Vec<AccountId>>,
+
+        /// id given to the next created auction, equal to the number of auctions
+        next_auction_id: u64,
+
+        /// bid log of every auction, by auction id and position in the log
+        bid_log: Mapping<(u64, u64), BidRecord>,
+
+        /// number of bids in the bid log of given auction
+        bid_log_len: Mapping<u64, u64>,
+
+        /// money in PSP22 tokens owed to accounts, by account and token
+        pending_token_withdrawals: Mapping<(AccountId, AccountId), u128>,
+
+        /// fraction of the highest bid that auction owner pays to cancel the auction, in basis points
+        cancellation_fee_bps: u32,
+
+        /// account proposed as the new contract owner, which still has to accept the ownership
+        proposed_owner: Option<AccountId>,
+
+        /// when the current pause started, None if the contract isn't paused
+        paused_since: Option<Timestamp>,
+
+        /// start and end of every finished pause, by its number
+        pauses: Mapping<u32, (Timestamp, Timestamp)>,
+
+        /// number of finished pauses
+        pause_count: u32,
+    }
+
+    /// new auction was created
+    #[ink(event)]
+    pub struct AuctionCreated {
+        #[ink(topic)]
+        pub auction_id: u64,
+        #[ink(topic)]
+        pub owner: AccountId,
+        pub starting_bid: u128,
+        pub finish_date: Timestamp,
+    }
+
+    /// bid was placed, amount is the bid made by the bidder
+    #[ink(event)]
+    pub struct BidPlaced {
+        #[ink(topic)]
+        pub auction_id: u64,
+        #[ink(topic)]
+        pub bidder: AccountId,
+        pub amount: u128,
+    }
+
+    /// bidder lost the lead, refund is the money credited back to the bidder
+    #[ink(event)]
+    pub struct Outbid {
+        #[ink(topic)]
+        pub auction_id: u64,
+        #[ink(topic)]
+        pub bidder: AccountId,
+        pub refund: u128,
+    }
+
+    /// auction was finished or bought right away
+    /// winner is None if nobody has bid or the reserve price wasn't met
+    #[ink(event)]
+    pub struct AuctionFinished {
+        #[ink(topic)]
+        pub auction_id: u64,
+        #[ink(topic)]
+        pub owner: AccountId,
+        #[ink(topic)]
+        pub winner: Option<AccountId>,
+        pub price: u128,
+        pub fee: u128,
+    }
+
+    /// auction was cancelled by its owner
+    #[ink(event)]
+    pub struct AuctionCancelled {
+        #[ink(topic)]
+        pub auction_id: u64,
+        #[ink(topic)]
+        pub owner: AccountId,
+        pub fee: u128,
+    }
+
+    /// nft of the auction was given to the contract's nft storage
+    #[ink(event)]
+    pub struct NftEscrowed {
+        #[ink(topic)]
+        pub auction_id: u64,
+        #[ink(topic)]
+        pub owner: AccountId,
+        pub nft_contract: AccountId,
+        pub token_id: Id,
+    }
+
+    /// nft of the auction was given from the contract's nft storage to the receiver
+    #[ink(event)]
+    pub struct NftReleased {
+        #[ink(topic)]
+        pub auction_id: u64,
+        #[ink(topic)]
+        pub receiver: AccountId,
+        pub nft_contract: AccountId,
+        pub token_id: Id,
+    }
+
+    /// contract owner changed the fees of new auctions
+    #[ink(event)]
+    pub struct FeesChanged {
+        pub create_auction_fee: u128,
+        pub settlement_fee_bps: u32,
+        pub cancellation_fee_bps: u32,
+    }
+
+    /// contract owner changed the allowed duration of new auctions
+    #[ink(event)]
+    pub struct DurationBoundsChanged {
+        pub min: u64,
+        pub max: u64,
+    }
+
+    /// contract owner proposed a new owner
+    #[ink(event)]
+    pub struct OwnershipProposed {
+        #[ink(topic)]
+        pub owner: AccountId,
+        #[ink(topic)]
+        pub proposed_owner: AccountId,
+    }
+
+    /// proposed owner accepted the ownership of the contract
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        pub previous_owner: AccountId,
+        #[ink(topic)]
+        pub owner: AccountId,
+    }
+
+    /// contract owner paused creating auctions and bidding
+    #[ink(event)]
+    pub struct Paused {
+        pub paused_at: Timestamp,
+    }
+
+    /// contract owner ended the pause, deadlines of running auctions moved by its length
+    #[ink(event)]
+    pub struct Unpaused {
+        pub paused_for: u64,
+    }
+
+    /// contract code was replaced, storage stays the same
+    #[ink(event)]
+    pub struct CodeUpgraded {
+        pub code_hash: [u8; 32],
     }
 
     /// result type
@@ -65,67 +420,262 @@ mod alleblock {
 
     impl Alleblock {
         /// constructor setting the fees
-        /// finalize_fee shouldn't be set to 0
+        /// settlement_fee_bps and cancellation_fee_bps are in basis points and can't exceed 10_000
+        /// also need to give address of friendly nft storage, whose owner this contract will be
         #[ink(constructor)]
-        pub fn new(create_auction_fee: u128, _finalize_fee_interest: u32, contract_owner: AccountId) -> Self {
-            let finalize_fee_interest = if _finalize_fee_interest == 0 {1} else {_finalize_fee_interest};
-            Self { 
-                auctions: Vec::new(),
-                create_auction_fee,
-                finalize_fee_interest,
-                contract_owner,
-            }
+        pub fn new(create_auction_fee: u128, settlement_fee_bps: u32, cancellation_fee_bps: u32, contract_owner: AccountId, nft_storage: AccountId) -> Self {
+            assert!(settlement_fee_bps <= MAX_FEE_BPS && cancellation_fee_bps <= MAX_FEE_BPS, "fee can't exceed 10_000 basis points");
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                contract.create_auction_fee = create_auction_fee;
+                contract.settlement_fee_bps = settlement_fee_bps;
+                contract.cancellation_fee_bps = cancellation_fee_bps;
+                contract.contract_owner = contract_owner;
+                contract.nft_storage = nft_storage;
+                ink_env::set_contract_storage_return_size(&Key::from(STORAGE_VERSION_KEY), &STORAGE_VERSION);
+            })
         }
 
         /// message used to create a brand new auction
         /// starting_bid -- lowest price at which the item can be sold (in the smallest chunk of currency, eg. picoTZERO)
         /// description -- description of item or service
         /// duration -- duration of auction in miliseconds, after creating the auction, everyone can bid for <duration> seconds
+        /// nft_contract -- account of the origin contract of the nft to be auctioned (None if not selling nft)
+        /// token_id -- id of the token to be auctioned (None if not selling nft)
+        /// soft_close -- settings for extending the auction after late bids (None if auction should end at fixed date)
+        /// duration together with the maximal soft close extension has to fit in the duration bounds, see get_duration_bounds
+        /// buy_now -- price for which the item can be bought right away (None if it can only be won by bidding)
+        /// min_increment -- how much every bid has to raise the highest bid (None if any raise is enough)
+        /// reserve_hash -- hidden reserve price, see get_reserve_hash (None if there is no reserve price)
+        /// payment_token -- PSP22 token in which the auction is paid, see bid_in_token (None if paid in the native currency)
+        /// Note: if you want to create auction with nft, you first need to allow auction contract to transfer it
+        #[ink(message, payable)]
+        pub fn create_auction(
+            &mut self, 
+            starting_bid: u128,
+            description: String,
+            duration: u64, 
+            nft_contract: Option<AccountId>,
+            token_id: Option<Id>,
+            soft_close: Option<SoftClose>,
+            buy_now: Option<BuyNow>,
+            min_increment: Option<BidIncrement>,
+            reserve_hash: Option<Hash>,
+            payment_token: Option<AccountId>
+        ) -> Result<u64> {
+            // buy-now price can't be lower than the starting bid
+            if buy_now.as_ref().map_or(false, |offer| offer.price < starting_bid) {
+                return Err(Error::InvalidPriceError);
+            }
+
+            return self.add_auction(AuctionKind::English, starting_bid, description, duration, nft_contract, token_id, soft_close, buy_now, min_increment, reserve_hash, payment_token);
+        }
+
+        /// message used to create a brand new dutch auction
+        /// start_price -- price right after creating the auction
+        /// floor_price -- lowest price at which the item can be sold, reached at the finish date
+        /// description -- description of item or service
+        /// duration -- duration of auction in miliseconds
+        /// nft_contract -- account of the origin contract of the nft to be auctioned (None if not selling nft)
+        /// token_id -- id of the token to be auctioned (None if not selling nft)
+        /// Note: if you want to create auction with nft, you first need to allow auction contract to transfer it
+        #[ink(message, payable)]
+        pub fn create_dutch_auction(
+            &mut self,
+            start_price: u128,
+            floor_price: u128,
+            description: String,
+            duration: u64,
+            nft_contract: Option<AccountId>,
+            token_id: Option<Id>
+        ) -> Result<u64> {
+            // price can only fall
+            if floor_price > start_price {
+                return Err(Error::InvalidPriceError);
+            }
+
+            return self.add_auction(AuctionKind::Dutch { start_price, floor_price }, floor_price, description, duration, nft_contract, token_id, None, None, None, None, None);
+        }
+
+        /// message used to create a brand new sealed-bid auction
+        /// starting_bid -- lowest price at which the item can be sold
+        /// description -- description of item or service
+        /// commit_duration -- for how many miliseconds bidders can commit their bids
+        /// reveal_duration -- for how many miliseconds after the commit phase bidders can reveal their bids
+        /// pricing -- whether the winner pays the first or the second price
+        /// unrevealed -- whether deposits of unrevealed bids are refunded or given to the auction owner
+        /// nft_contract -- account of the origin contract of the nft to be auctioned (None if not selling nft)
+        /// token_id -- id of the token to be auctioned (None if not selling nft)
+        /// Note: if you want to create auction with nft, you first need to allow auction contract to transfer it
         #[ink(message, payable)]
-        pub fn create_auction(&mut self, starting_bid: u128, description: String, duration: u64) -> Result<u64> {
+        pub fn create_sealed_auction(
+            &mut self,
+            starting_bid: u128,
+            description: String,
+            commit_duration: u64,
+            reveal_duration: u64,
+            pricing: SealedPricing,
+            unrevealed: UnrevealedDeposit,
+            nft_contract: Option<AccountId>,
+            token_id: Option<Id>
+        ) -> Result<u64> {
+            // nobody could commit or reveal a bid in a phase without time
+            if commit_duration == 0 || reveal_duration == 0 {
+                return Err(Error::InvalidDurationError);
+            }
+            let duration = commit_duration.checked_add(reveal_duration).ok_or(Error::InvalidDurationError)?;
+            let reveal_date = self.env().block_timestamp().checked_add(commit_duration).ok_or(Error::InvalidDurationError)?;
+            let kind = AuctionKind::Sealed { pricing, reveal_date, unrevealed };
+
+            return self.add_auction(kind, starting_bid, description, duration, nft_contract, token_id, None, None, None, None, None);
+        }
+
+        /// charge the creation fee, freeze the nft and store a new auction
+        fn add_auction(
+            &mut self,
+            kind: AuctionKind,
+            starting_bid: u128,
+            description: String,
+            duration: u64,
+            nft_contract: Option<AccountId>,
+            token_id: Option<Id>,
+            soft_close: Option<SoftClose>,
+            buy_now: Option<BuyNow>,
+            min_increment: Option<BidIncrement>,
+            reserve_hash: Option<Hash>,
+            payment_token: Option<AccountId>
+        ) -> Result<u64> {
+            return self.non_reentrant(|contract| contract.try_add_auction(kind, starting_bid, description, duration, nft_contract, token_id, soft_close, buy_now, min_increment, reserve_hash, payment_token));
+        }
+
+        /// add_auction, run under the reentrancy lock
+        fn try_add_auction(
+            &mut self,
+            kind: AuctionKind,
+            starting_bid: u128,
+            description: String,
+            duration: u64,
+            nft_contract: Option<AccountId>,
+            token_id: Option<Id>,
+            soft_close: Option<SoftClose>,
+            buy_now: Option<BuyNow>,
+            min_increment: Option<BidIncrement>,
+            reserve_hash: Option<Hash>,
+            payment_token: Option<AccountId>
+        ) -> Result<u64> {
             let transferred_value = self.env().transferred_value();
+            let owner = self.env().caller();
+
+            let nft_contract_account: Option<AccountId>;
+            let nft_token_id: Option<Id>;
+
+            // no new auctions during a pause
+            if self.paused_since.is_some() {
+                return Err(Error::PausedError);
+            }
 
             // check if paid fee is high enough
             if self.create_auction_fee > transferred_value {
                 return Err(Error::TooLowFeeError);
             }
 
-            // transfer fee to the contract owner
-            if self.env().transfer(self.contract_owner, transferred_value).is_err() {
-                return Err(Error::TransferError);
+            // if nft is auctioned
+            if nft_contract.is_some() && token_id.is_some() {
+                nft_contract_account = nft_contract;
+                nft_token_id = token_id;
+                let unwrapped_account = nft_contract_account.clone().unwrap();
+
+                // check if contract has allowance to take the token
+                if !self.nft_allowance(unwrapped_account, owner, nft_token_id.clone().unwrap()) {
+                    return Err(Error::NoNftAllowanceError);
+                }
             }
 
-            // create new auction
+            // if no nft is auctioned
+            else {
+                nft_contract_account = None;
+                nft_token_id = None;
+            }
+
+            // auction has to end, but not too late, see get_duration_bounds
+            // soft close can't push the finish date out of range either
             let creation_date = self.env().block_timestamp();
-            let finish_date = creation_date + duration;
-            let auction_id = self.auctions.len() as u64;
+            let finish_date = alleblock_core::finish_date(creation_date, duration, &self.duration_bounds(), &soft_close)?;
+
+            // create new auction
+            let auction_id = self.next_auction_id;
+            let auction_state = match kind {
+                AuctionKind::Sealed { .. } => AuctionState::Commit,
+                _ => AuctionState::InProgress
+            };
 
             let fresh_auction = AuctionInfo {
                 id: auction_id,
-                owner: self.env().caller(),
+                owner,
                 description,
                 starting_bid,
                 highest_bid: 0,
-                highest_bidder: self.env().caller(),
+                highest_bidder_max: 0,
+                highest_bidder: owner,
                 creation_date,
                 finish_date,
-                auction_state: AuctionState::InProgress
+                auction_state,
+                nft_contract_account,
+                nft_token_id,
+                soft_close,
+                extended_by: 0,
+                kind,
+                second_highest_bid: 0,
+                buy_now,
+                min_increment,
+                reserve_hash,
+                reserve: None,
+                payment_token,
+                fee_terms: self.fee_terms(),
+                pause_index: self.pause_count,
             };
 
-            self.auctions.push(fresh_auction);
+            self.save_auction(&fresh_auction);
+            self.next_auction_id += 1;
+
+            // credit the fee to the contract owner
+            self.credit(None, self.contract_owner, transferred_value);
+
+            // freeze the nft in the contract account
+            if let (Some(nft_contract), Some(token_id)) = (fresh_auction.nft_contract_account, fresh_auction.nft_token_id.clone()) {
+                if self.escrow_nft(nft_contract, token_id).is_err() {
+                    return Err(Error::NftTransferError);
+                }
+            }
+
+            self.emit_event(AuctionCreated { auction_id, owner, starting_bid, finish_date });
+            if let (Some(nft_contract), Some(token_id)) = (fresh_auction.nft_contract_account, fresh_auction.nft_token_id) {
+                self.emit_event(NftEscrowed { auction_id, owner, nft_contract, token_id });
+            }
 
             return Ok(auction_id);
         }
 
-
-        /// try to bid auction with given id
+        /// buy the item right away
+        /// dutch auction is bought for its current price, english auction for its buy-now price
+        /// money transferred above the price is given back to the buyer, the highest bid is given back to the bidder
+        /// changes the auction state to Finished
         #[ink(message, payable)]
-        pub fn bid(&mut self, auction_id: u64) -> Result<()> {
+        pub fn buy_now(&mut self, auction_id: u64) -> Result<()> {
+            return self.non_reentrant(|contract| contract.try_buy_now(auction_id));
+        }
+
+        /// buy_now, run under the reentrancy lock
+        fn try_buy_now(&mut self, auction_id: u64) -> Result<()> {
             let caller = self.env().caller();
             let transferred_value = self.env().transferred_value();
             let block_timestamp = self.env().block_timestamp();
 
-            let auction = match self.auctions.get(auction_id as usize) {
+            // no bids during a pause
+            if self.paused_since.is_some() {
+                return Err(Error::PausedError);
+            }
+
+            let auction = match self.load_auction(auction_id) {
                 Some(x) => x,
                 None => return Err(Error::NoSuchAuctionError)
             };
@@ -141,172 +691,1248 @@ mod alleblock {
             }
 
             // check if enough money is transferred
-            if transferred_value <= auction.highest_bid || transferred_value < auction.starting_bid {
-                return Err(Error::TooLowBidError);
+            let price = Self::buy_now_price(&auction, block_timestamp)?;
+            if auction.payment_token.is_none() && transferred_value < price {
+                return Err(Error::TooLowBidError(price));
             }
 
-            // transfer money back to the previous bidder
+            let service_fee = alleblock_core::fee_of(price, auction.fee_terms.settlement_fee_bps);
+
+            // give the money back to the highest bidder
             if auction.highest_bid > 0 {
-                if self.env().transfer(auction.highest_bidder, auction.highest_bid).is_err() {
-                    return Err(Error::TransferError);
-                }
+                self.credit(auction.payment_token, auction.highest_bidder, auction.highest_bidder_max);
             }
 
+            // give money to the auction owner, fee to the contract owner and the native currency change to the buyer
+            self.credit(auction.payment_token, auction.owner, price - service_fee);
+            self.credit(auction.payment_token, self.contract_owner, service_fee);
+            let change = if auction.payment_token.is_some() { transferred_value } else { transferred_value - price };
+            self.credit(None, caller, change);
+
             // update auction data
-            let auction_mut = match self.auctions.get_mut(auction_id as usize) {
+            let mut auction_mut = match self.load_auction(auction_id) {
                 Some(x) => x,
                 None => return Err(Error::NoSuchAuctionError)
             };
             auction_mut.highest_bidder = caller;
-            auction_mut.highest_bid = transferred_value;
+            auction_mut.highest_bid = price;
+            auction_mut.highest_bidder_max = price;
+            auction_mut.auction_state = AuctionState::Finished;
+            self.save_auction(&auction_mut);
+
+            self.log_bid(auction_id, caller, price);
+
+            // take the price in the payment token, the buyer has to approve it first
+            if let Some(payment_token) = auction.payment_token {
+                if let Err(error) = self.take_token(payment_token, caller, price) {
+                    return Err(Error::TokenTransferError(error));
+                }
+            }
+
+            // send nft to the buyer
+            self.release_nft(&auction, caller)?;
+
+            if auction.highest_bid > 0 {
+                self.emit_event(Outbid { auction_id, bidder: auction.highest_bidder, refund: auction.highest_bidder_max });
+            }
+            self.emit_event(AuctionFinished { auction_id, owner: auction.owner, winner: Some(caller), price, fee: service_fee });
 
             return Ok(());
         }
 
 
-        /// finish an auction, so the owner gets the auction money
-        /// can be called only after the auction's finish date
-        /// changes the auction state to Finished
+        /// try to bid auction with given id
+        /// returns the finish date of the auction, which may be extended by a late bid
+        #[ink(message, payable)]
+        pub fn bid(&mut self, auction_id: u64) -> Result<Timestamp> {
+            let caller = self.env().caller();
+            let transferred_value = self.env().transferred_value();
+            return self.place_bid(auction_id, caller, transferred_value, false, false);
+        }
+
+        /// bid <amount> of the payment token on auction with given id
+        /// the bidder has to approve the amount to this contract first
+        /// returns the finish date of the auction, which may be extended by a late bid
         #[ink(message)]
-        pub fn finish_auction(&mut self, auction_id: u64) -> Result<()> {
+        pub fn bid_in_token(&mut self, auction_id: u64, amount: u128) -> Result<Timestamp> {
+            let caller = self.env().caller();
+            return self.place_bid(auction_id, caller, amount, false, true);
+        }
+
+        /// place a proxy bid on auction with given id, transferred money is the maximal bid
+        /// the bid is raised by the minimal increment whenever someone outbids it, up to the maximum
+        /// the winner pays only the reached bid and gets the rest of the maximum back
+        /// returns the finish date of the auction, which may be extended by a late bid
+        #[ink(message, payable)]
+        pub fn place_proxy_bid(&mut self, auction_id: u64) -> Result<Timestamp> {
+            let caller = self.env().caller();
+            let transferred_value = self.env().transferred_value();
+            return self.place_bid(auction_id, caller, transferred_value, true, false);
+        }
+
+        /// place a proxy bid of at most <max_amount> of the payment token on auction with given id
+        /// the bidder has to approve the maximal amount to this contract first, see place_proxy_bid
+        /// returns the finish date of the auction, which may be extended by a late bid
+        #[ink(message)]
+        pub fn place_proxy_bid_in_token(&mut self, auction_id: u64, max_amount: u128) -> Result<Timestamp> {
+            let caller = self.env().caller();
+            return self.place_bid(auction_id, caller, max_amount, true, true);
+        }
+
+        /// bid <amount> on an english auction, as a maximal bid if <proxy> is set
+        /// amount is taken from the bidder's payment token if <in_token> is set, otherwise it was transferred
+        fn place_bid(&mut self, auction_id: u64, bidder: AccountId, amount: u128, proxy: bool, in_token: bool) -> Result<Timestamp> {
+            return self.non_reentrant(|contract| contract.try_place_bid(auction_id, bidder, amount, proxy, in_token));
+        }
+
+        /// place_bid, run under the reentrancy lock
+        fn try_place_bid(&mut self, auction_id: u64, bidder: AccountId, amount: u128, proxy: bool, in_token: bool) -> Result<Timestamp> {
             let block_timestamp = self.env().block_timestamp();
 
-            let auction = match self.auctions.get(auction_id as usize) {
+            // no bids during a pause
+            if self.paused_since.is_some() {
+                return Err(Error::PausedError);
+            }
+
+            let auction = match self.load_auction(auction_id) {
                 Some(x) => x,
                 None => return Err(Error::NoSuchAuctionError)
             };
 
+            // dutch auctions are won with buy_now, sealed-bid auctions with commit_bid and reveal_bid
+            if auction.kind != AuctionKind::English {
+                return Err(Error::WrongAuctionKindError);
+            }
+
             // check if auction is in progress
             if auction.auction_state != AuctionState::InProgress {
                 return Err(Error::AuctionNotInProgressError);
             }
 
-            // perform only after auction finish date
-            if block_timestamp <= auction.finish_date {
-                return Err(Error::BeforeFinishDateError);
+            // perform only before auction finish date
+            if block_timestamp > auction.finish_date {
+                return Err(Error::AfterFinishDateError);
             }
 
-            // if anyone bid the auction
-            if auction.highest_bid > 0 {
-                let service_fee = auction.highest_bid.div_euclid(self.finalize_fee_interest as u128);
+            // bid has to be paid in the auction's currency
+            if auction.payment_token.is_some() != in_token {
+                return Err(Error::WrongCurrencyError);
+            }
 
-                // transfer money to the auction owner
-                if self.env().transfer(auction.owner, auction.highest_bid - service_fee).is_err() {
-                    return Err(Error::TransferError);
-                }
+            // check if enough money is transferred, proxy bids are raised by the rules of alleblock_core
+            let lead = Lead { bidder: auction.highest_bidder, bid: auction.highest_bid, max: auction.highest_bidder_max };
+            let outcome = alleblock_core::place_bid(lead, auction.starting_bid, &auction.min_increment, bidder, amount, proxy)?;
 
-                // transfer fee to the contract owner
-                if self.env().transfer(self.contract_owner, service_fee).is_err() {
-                    return Err(Error::TransferError);
-                }
+            // give back the money which isn't bid anymore
+            if let Some((account, refund)) = outcome.refund {
+                self.credit(auction.payment_token, account, refund);
             }
+            let outbid = if outcome.outbid { outcome.refund } else { None };
+            let (highest_bidder, highest_bid, highest_bidder_max) = (outcome.lead.bidder, outcome.lead.bid, outcome.lead.max);
 
             // update auction data
-            let auction_mut = match self.auctions.get_mut(auction_id as usize) {
+            let mut auction_mut = match self.load_auction(auction_id) {
                 Some(x) => x,
                 None => return Err(Error::NoSuchAuctionError)
             };
-            auction_mut.auction_state = AuctionState::Finished;
+            auction_mut.highest_bidder = highest_bidder;
+            auction_mut.highest_bid = highest_bid;
+            auction_mut.highest_bidder_max = highest_bidder_max;
 
-            return Ok(());
+            // extend the auction if the bid came in the soft close window
+            if let Some(soft_close) = &auction_mut.soft_close {
+                (auction_mut.finish_date, auction_mut.extended_by) = alleblock_core::extend(soft_close, auction_mut.finish_date, auction_mut.extended_by, block_timestamp)?;
+            }
+            self.save_auction(&auction_mut);
+
+            // take the bid in the payment token, the bidder has to approve it first
+            if let Some(payment_token) = auction.payment_token {
+                if let Err(error) = self.take_token(payment_token, bidder, amount) {
+                    return Err(Error::TokenTransferError(error));
+                }
+            }
+
+            let amount = if highest_bidder == bidder { highest_bid } else { amount };
+            self.log_bid(auction_id, bidder, amount);
+            self.emit_event(BidPlaced { auction_id, bidder, amount });
+            if let Some((bidder, refund)) = outbid {
+                self.emit_event(Outbid { auction_id, bidder, refund });
+            }
+
+            return Ok(auction_mut.finish_date);
         }
 
-        /// cancel an auction 
-        /// only auction owner can call this message
-        /// money is returned to the bidder
-        /// owner has to pay the fee
-        /// changes auction stated to Cancelled
+        /// commit a sealed bid to the auction with given id
+        /// hash -- blake2x256 of scale encoded (auction_id, bidder, amount, salt), see get_sealed_bid_hash
+        /// transferred money is the deposit, it has to cover the bid revealed later
+        /// committing again replaces the hash and adds to the deposit
         #[ink(message, payable)]
-        pub fn cancel_auction(&mut self, auction_id: u64) -> Result<()> {
+        pub fn commit_bid(&mut self, auction_id: u64, hash: Hash) -> Result<()> {
+            return self.non_reentrant(|contract| contract.try_commit_bid(auction_id, hash));
+        }
+
+        /// commit_bid, run under the reentrancy lock
+        fn try_commit_bid(&mut self, auction_id: u64, hash: Hash) -> Result<()> {
             let caller = self.env().caller();
             let transferred_value = self.env().transferred_value();
             let block_timestamp = self.env().block_timestamp();
 
-            let auction = match self.auctions.get(auction_id as usize) {
+            // no bids during a pause
+            if self.paused_since.is_some() {
+                return Err(Error::PausedError);
+            }
+
+            let auction = match self.load_auction(auction_id) {
                 Some(x) => x,
                 None => return Err(Error::NoSuchAuctionError)
             };
 
-            // check if auction is in progress
-            if auction.auction_state != AuctionState::InProgress {
-                return Err(Error::AuctionNotInProgressError);
-            }
-
-            // check if auction owner is the caller
-            if caller != auction.owner {
-                return Err(Error::NotAnOwnerError);
-            }
+            let reveal_date = match auction.kind {
+                AuctionKind::Sealed { reveal_date, .. } => reveal_date,
+                _ => return Err(Error::WrongAuctionKindError)
+            };
 
-            // perform only before auction finish date
-            if block_timestamp > auction.finish_date {
-                return Err(Error::AfterFinishDateError);
+            // perform only in the commit phase
+            if auction.auction_state != AuctionState::Commit || block_timestamp > reveal_date {
+                return Err(Error::NotCommitPhaseError);
             }
 
-            // if anyone has bid an auction
-            if auction.highest_bid > 0 {
-                // check if fee is high enough
-                let service_fee = auction.highest_bid.div_euclid(self.finalize_fee_interest as u128);
-                if transferred_value < service_fee {
-                    return Err(Error::TooLowFeeError);
-                }
-
-                // return the money to the highest bidder
-                if self.env().transfer(auction.highest_bidder, auction.highest_bid).is_err() {
-                    return Err(Error::TransferError);
+            let mut bidders = self.sealed_bidders.get(auction_id).unwrap_or_default();
+            let sealed_bid = match self.sealed_bids.get((auction_id, caller)) {
+                Some(previous) => SealedBid { hash, deposit: previous.deposit + transferred_value, revealed: false },
+                None => {
+                    if bidders.len() >= MAX_SEALED_BIDS {
+                        return Err(Error::TooManyBidsError);
+                    }
+                    bidders.push(caller);
+                    SealedBid { hash, deposit: transferred_value, revealed: false }
                 }
-            }
+            };
 
-            // transfer fee to the owner
-            if self.env().transfer(self.contract_owner, transferred_value).is_err() {
-                return Err(Error::TransferError);
+            // deposit has to cover at least the starting bid
+            if sealed_bid.deposit < auction.starting_bid {
+                return Err(Error::TooLowBidError(auction.starting_bid));
             }
 
-            // update auction data
-            let auction_mut = match self.auctions.get_mut(auction_id as usize) {
-                Some(x) => x,
-                None => return Err(Error::NoSuchAuctionError)
-            };
-            auction_mut.auction_state = AuctionState::Cancelled;
+            self.sealed_bids.insert((auction_id, caller), &sealed_bid);
+            self.sealed_bidders.insert(auction_id, &bidders);
 
             return Ok(());
         }
 
-        /// return list of all the auctions
+        /// reveal the sealed bid committed to the auction with given id
+        /// amount and salt have to match the committed hash
+        /// deposit above the amount is given back right away, whole deposit is given back when the bid is outbid
         #[ink(message)]
-        pub fn get_auctions(&self) -> Vec<AuctionInfo> {
-            return self.auctions.clone();
+        pub fn reveal_bid(&mut self, auction_id: u64, amount: u128, salt: [u8; 32]) -> Result<()> {
+            return self.non_reentrant(|contract| contract.try_reveal_bid(auction_id, amount, salt));
         }
 
-        /// return the fee needed to crate an auction
-        #[ink(message)]
-        pub fn get_create_auction_fee(&self) -> u128 {
-            return self.create_auction_fee.clone();
-        }
+        /// reveal_bid, run under the reentrancy lock
+        fn try_reveal_bid(&mut self, auction_id: u64, amount: u128, salt: [u8; 32]) -> Result<()> {
+            let caller = self.env().caller();
+            let block_timestamp = self.env().block_timestamp();
 
-        /// return the fee interest taken from finalized auction
-        #[ink(message)]
-        pub fn get_finalize_fee_interest(&self) -> u32 {
-            return self.finalize_fee_interest.clone();
-        }
+            // no bids during a pause
+            if self.paused_since.is_some() {
+                return Err(Error::PausedError);
+            }
 
-        /// return the fee taken when finalizing particular auction
-        #[ink(message)]
-        pub fn get_finalize_fee_of(&self, auction_id: u64) -> Result<u128> {
-            let auction = match self.auctions.get(auction_id as usize) {
+            let auction = match self.load_auction(auction_id) {
                 Some(x) => x,
                 None => return Err(Error::NoSuchAuctionError)
             };
-            return Ok(auction.highest_bid.div_euclid(self.finalize_fee_interest as u128));
-        }
 
-        /// return owner of the contract who receives all the fees
-        #[ink(message)]
-        pub fn get_contract_owner(&self) -> AccountId {
-            return self.contract_owner.clone();
-        }
-    }
+            let reveal_date = match auction.kind {
+                AuctionKind::Sealed { reveal_date, .. } => reveal_date,
+                _ => return Err(Error::WrongAuctionKindError)
+            };
 
-}
+            // perform only in the reveal phase, the first reveal starts it
+            let in_reveal_phase = auction.auction_state == AuctionState::Reveal
+                || auction.auction_state == AuctionState::Commit;
+            if !in_reveal_phase || block_timestamp <= reveal_date || block_timestamp > auction.finish_date {
+                return Err(Error::NotRevealPhaseError);
+            }
+
+            let mut sealed_bid = match self.sealed_bids.get((auction_id, caller)) {
+                Some(x) if !x.revealed => x,
+                _ => return Err(Error::NoSuchBidError)
+            };
+
+            // check if bid matches the commitment
+            if self.get_sealed_bid_hash(auction_id, caller, amount, salt) != sealed_bid.hash || amount > sealed_bid.deposit {
+                return Err(Error::InvalidRevealError);
+            }
+            if amount < auction.starting_bid {
+                return Err(Error::TooLowBidError(auction.starting_bid));
+            }
 
+            let (highest_bidder, highest_bid) = (auction.highest_bidder, auction.highest_bid);
+            let outbids = amount > highest_bid;
+
+            sealed_bid.revealed = true;
+            self.sealed_bids.insert((auction_id, caller), &sealed_bid);
+
+            // give back the deposit which isn't needed anymore
+            if outbids {
+                self.credit(auction.payment_token, highest_bidder, highest_bid);
+                self.credit(auction.payment_token, caller, sealed_bid.deposit - amount);
+            } else {
+                self.credit(auction.payment_token, caller, sealed_bid.deposit);
+            }
+
+            // update auction data
+            let mut auction_mut = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+            auction_mut.auction_state = AuctionState::Reveal;
+            if outbids {
+                auction_mut.second_highest_bid = highest_bid;
+                auction_mut.highest_bidder = caller;
+                auction_mut.highest_bid = amount;
+                auction_mut.highest_bidder_max = amount;
+            } else if amount > auction_mut.second_highest_bid {
+                auction_mut.second_highest_bid = amount;
+            }
+            self.save_auction(&auction_mut);
+
+            self.log_bid(auction_id, caller, amount);
+            self.emit_event(BidPlaced { auction_id, bidder: caller, amount });
+            if !outbids {
+                self.emit_event(Outbid { auction_id, bidder: caller, refund: sealed_bid.deposit });
+            } else if highest_bid > 0 {
+                self.emit_event(Outbid { auction_id, bidder: highest_bidder, refund: highest_bid });
+            }
+
+            return Ok(());
+        }
+
+
+        /// finish an auction, so the owner gets the auction money
+        /// can be called only after the auction's finish date
+        /// changes the auction state to Finished
+        /// if the reserve price wasn't revealed or reached, the highest bid is given back
+        /// and the auction state changes to ReserveNotMet instead
+        #[ink(message)]
+        pub fn finish_auction(&mut self, auction_id: u64) -> Result<()> {
+            return self.non_reentrant(|contract| contract.try_finish_auction(auction_id));
+        }
+
+        /// finish_auction, run under the reentrancy lock
+        fn try_finish_auction(&mut self, auction_id: u64) -> Result<()> {
+            let auction = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+
+            // check if auction is in progress
+            if !auction.auction_state.is_open() {
+                return Err(Error::AuctionNotInProgressError);
+            }
+
+            // perform only after auction finish date
+            if !self.deadline_passed(auction.finish_date) {
+                return Err(Error::BeforeFinishDateError);
+            }
+
+            let finished = FinishedAuction {
+                kind: &auction.kind,
+                starting_bid: auction.starting_bid,
+                highest_bid: auction.highest_bid,
+                highest_bidder_max: auction.highest_bidder_max,
+                second_highest_bid: auction.second_highest_bid,
+                reserve: auction.reserve,
+                reserve_committed: auction.reserve_hash.is_some(),
+            };
+            let settlement = alleblock_core::settle(&finished, &auction.fee_terms);
+
+            // check if the reserve price was revealed and met
+            if !settlement.reserve_met {
+                // give the money back to the highest bidder
+                if settlement.winner_refund > 0 {
+                    self.credit(auction.payment_token, auction.highest_bidder, settlement.winner_refund);
+                }
+
+                let mut auction_mut = match self.load_auction(auction_id) {
+                    Some(x) => x,
+                    None => return Err(Error::NoSuchAuctionError)
+                };
+                auction_mut.auction_state = AuctionState::ReserveNotMet;
+                self.save_auction(&auction_mut);
+
+                // send nft back to the auction owner
+                self.release_nft(&auction, auction.owner)?;
+
+                self.emit_event(AuctionFinished { auction_id, owner: auction.owner, winner: None, price: 0, fee: 0 });
+
+                return Ok(());
+            }
+
+            // deal with deposits of bids which were never revealed
+            if let AuctionKind::Sealed { unrevealed, .. } = &auction.kind {
+                let receiver = match unrevealed {
+                    UnrevealedDeposit::Refund => None,
+                    UnrevealedDeposit::Forfeit => Some(auction.owner)
+                };
+                self.settle_unrevealed_bids(auction_id, receiver);
+            }
+
+            // give the winner back what wasn't needed to pay the price
+            if settlement.winner_refund > 0 {
+                self.credit(auction.payment_token, auction.highest_bidder, settlement.winner_refund);
+            }
+
+            // if anyone bid the auction
+            let (price, service_fee) = (settlement.price, settlement.platform_fee);
+            if price > 0 {
+
+                // give money to the auction owner
+                self.credit(auction.payment_token, auction.owner, settlement.seller_amount);
+
+                // give fee to the contract owner
+                self.credit(auction.payment_token, self.contract_owner, service_fee);
+            }
+
+            // update auction data
+            let mut auction_mut = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+            auction_mut.auction_state = AuctionState::Finished;
+            self.save_auction(&auction_mut);
+
+            // send nft to the winner, the auction owner is the highest bidder if nobody has bid
+            self.release_nft(&auction, auction.highest_bidder)?;
+
+            let winner = if auction.highest_bid > 0 { Some(auction.highest_bidder) } else { None };
+            self.emit_event(AuctionFinished { auction_id, owner: auction.owner, winner, price, fee: service_fee });
+
+            return Ok(());
+        }
+
+        /// cancel an auction 
+        /// only auction owner can call this message
+        /// money is returned to the bidder
+        /// owner has to pay the fee
+        /// changes auction stated to Cancelled
+        #[ink(message, payable)]
+        pub fn cancel_auction(&mut self, auction_id: u64) -> Result<()> {
+            return self.non_reentrant(|contract| contract.try_cancel_auction(auction_id));
+        }
+
+        /// cancel_auction, run under the reentrancy lock
+        fn try_cancel_auction(&mut self, auction_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+            let transferred_value = self.env().transferred_value();
+
+            let auction = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+
+            // check if auction is in progress
+            if !auction.auction_state.is_open() {
+                return Err(Error::AuctionNotInProgressError);
+            }
+
+            // check if auction owner is the caller
+            if caller != auction.owner {
+                return Err(Error::NotAnOwnerError);
+            }
+
+            // perform only before auction finish date
+            if self.deadline_passed(auction.finish_date) {
+                return Err(Error::AfterFinishDateError);
+            }
+
+            // fee of an auction paid in PSP22 token is paid in that token
+            if auction.payment_token.is_some() && transferred_value > 0 {
+                return Err(Error::WrongCurrencyError);
+            }
+
+            // if anyone has bid an auction, check if fee is high enough
+            let service_fee = alleblock_core::fee_of(auction.highest_bid, auction.fee_terms.cancellation_fee_bps);
+            if auction.payment_token.is_none() && transferred_value < service_fee {
+                return Err(Error::TooLowFeeError);
+            }
+
+            // give the money back to the highest bidder
+            if auction.highest_bid > 0 {
+                self.credit(auction.payment_token, auction.highest_bidder, auction.highest_bidder_max);
+            }
+
+            // give back deposits of sealed bids which were not revealed yet
+            self.settle_unrevealed_bids(auction_id, None);
+
+            // give fee to the contract owner
+            let fee = if auction.payment_token.is_some() { service_fee } else { transferred_value };
+            self.credit(auction.payment_token, self.contract_owner, fee);
+
+            // update auction data
+            let mut auction_mut = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+            auction_mut.auction_state = AuctionState::Cancelled;
+            self.save_auction(&auction_mut);
+
+            // take the fee in the payment token, the owner has to approve it first
+            if let Some(payment_token) = auction.payment_token.filter(|_| service_fee > 0) {
+                if let Err(error) = self.take_token(payment_token, caller, service_fee) {
+                    return Err(Error::TokenTransferError(error));
+                }
+            }
+
+            // return nft to the auction owner
+            self.release_nft(&auction, auction.owner)?;
+
+            self.emit_event(AuctionCancelled { auction_id, owner: caller, fee });
+
+            return Ok(());
+        }
+
+        /// reveal the hidden reserve price of an auction
+        /// only auction owner can call this message, before the auction's finish date
+        /// reserve and salt have to match the reserve hash given when creating the auction
+        /// Note: reserve which isn't revealed is treated as not met, so the auction ends without a sale
+        #[ink(message)]
+        pub fn reveal_reserve(&mut self, auction_id: u64, reserve: u128, salt: [u8; 32]) -> Result<()> {
+            return self.non_reentrant(|contract| contract.try_reveal_reserve(auction_id, reserve, salt));
+        }
+
+        /// reveal_reserve, run under the reentrancy lock
+        fn try_reveal_reserve(&mut self, auction_id: u64, reserve: u128, salt: [u8; 32]) -> Result<()> {
+            let caller = self.env().caller();
+
+            let auction = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+
+            // check if auction is in progress
+            if !auction.auction_state.is_open() {
+                return Err(Error::AuctionNotInProgressError);
+            }
+
+            // check if auction owner is the caller
+            if caller != auction.owner {
+                return Err(Error::NotAnOwnerError);
+            }
+
+            // reveal only while bidding, so the seller can't choose the outcome after seeing the final bids
+            if self.deadline_passed(auction.finish_date) {
+                return Err(Error::AfterFinishDateError);
+            }
+
+            // check if reserve matches the hash
+            if auction.reserve_hash != Some(self.get_reserve_hash(reserve, salt)) {
+                return Err(Error::InvalidRevealError);
+            }
+
+            // update auction data
+            let mut auction_mut = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+            auction_mut.reserve = Some(reserve);
+            self.save_auction(&auction_mut);
+
+            return Ok(());
+        }
+
+        /// transfer all the money owed to the caller
+        /// returns the withdrawn amount
+        #[ink(message)]
+        pub fn withdraw(&mut self) -> Result<u128> {
+            return self.non_reentrant(|contract| contract.try_withdraw());
+        }
+
+        /// withdraw, run under the reentrancy lock
+        fn try_withdraw(&mut self) -> Result<u128> {
+            let caller = self.env().caller();
+            let amount = self.get_pending_withdrawal(caller);
+
+            if amount == 0 {
+                return Err(Error::NothingToWithdrawError);
+            }
+
+            // clear the balance before transferring, restore it if transfer fails
+            self.pending_withdrawals.remove(caller);
+            if self.env().transfer(caller, amount).is_err() {
+                self.pending_withdrawals.insert(caller, &amount);
+                return Err(Error::TransferError);
+            }
+
+            return Ok(amount);
+        }
+
+        /// return the money waiting to be withdrawn by given account
+        #[ink(message)]
+        pub fn get_pending_withdrawal(&self, account: AccountId) -> u128 {
+            return self.pending_withdrawals.get(account).unwrap_or(0);
+        }
+
+        /// transfer all the money in given PSP22 token owed to the caller
+        /// returns the withdrawn amount
+        #[ink(message)]
+        pub fn withdraw_token(&mut self, payment_token: AccountId) -> Result<u128> {
+            return self.non_reentrant(|contract| contract.try_withdraw_token(payment_token));
+        }
+
+        /// withdraw_token, run under the reentrancy lock
+        fn try_withdraw_token(&mut self, payment_token: AccountId) -> Result<u128> {
+            let caller = self.env().caller();
+            let amount = self.get_pending_token_withdrawal(caller, payment_token);
+
+            if amount == 0 {
+                return Err(Error::NothingToWithdrawError);
+            }
+
+            // clear the balance before transferring, restore it if transfer fails
+            self.pending_token_withdrawals.remove((caller, payment_token));
+            if let Err(error) = self.send_token(payment_token, caller, amount) {
+                self.pending_token_withdrawals.insert((caller, payment_token), &amount);
+                return Err(Error::TokenTransferError(error));
+            }
+
+            return Ok(amount);
+        }
+
+        /// return the money in given PSP22 token waiting to be withdrawn by given account
+        #[ink(message)]
+        pub fn get_pending_token_withdrawal(&self, account: AccountId, payment_token: AccountId) -> u128 {
+            return self.pending_token_withdrawals.get((account, payment_token)).unwrap_or(0);
+        }
+
+        /// return the current price of a dutch auction
+        #[ink(message)]
+        pub fn get_current_price(&self, auction_id: u64) -> Result<u128> {
+            let auction = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+            return Self::dutch_price(&auction, self.price_timestamp());
+        }
+
+        /// return the hash to commit for a sealed bid
+        /// Note: queries may be seen by the node operator, compute the hash locally to keep the bid secret
+        #[ink(message)]
+        pub fn get_sealed_bid_hash(&self, auction_id: u64, bidder: AccountId, amount: u128, salt: [u8; 32]) -> Hash {
+            return Hash::from(self.env().hash_encoded::<Blake2x256, _>(&(auction_id, bidder, amount, salt)));
+        }
+
+        /// return the hash to give as a hidden reserve price
+        /// Note: queries may be seen by the node operator, compute the hash locally to keep the reserve secret
+        #[ink(message)]
+        pub fn get_reserve_hash(&self, reserve: u128, salt: [u8; 32]) -> Hash {
+            return Hash::from(self.env().hash_encoded::<Blake2x256, _>(&(reserve, salt)));
+        }
+
+        /// return the sealed bid committed by given bidder
+        #[ink(message)]
+        pub fn get_sealed_bid(&self, auction_id: u64, bidder: AccountId) -> Option<SealedBid> {
+            return self.sealed_bids.get((auction_id, bidder));
+        }
+
+        /// return the price for which the item can be bought right away
+        /// fails with BuyNowUnavailableError if the offer was withdrawn, so clients can hide it
+        #[ink(message)]
+        pub fn get_buy_now_price(&self, auction_id: u64) -> Result<u128> {
+            let auction = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+            return Self::buy_now_price(&auction, self.price_timestamp());
+        }
+
+        /// return the lowest amount which can be bid on an english auction
+        #[ink(message)]
+        pub fn get_min_next_bid(&self, auction_id: u64) -> Result<u128> {
+            let auction = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+            if auction.kind != AuctionKind::English {
+                return Err(Error::WrongAuctionKindError);
+            }
+            return Ok(Self::min_next_bid(&auction));
+        }
+
+        /// return list of all the auctions
+        #[ink(message)]
+        pub fn get_auctions(&self) -> Vec<AuctionInfo> {
+            return (0..self.next_auction_id).filter_map(|auction_id| self.load_auction(auction_id)).collect();
+        }
+
+        /// return at most <limit> auctions matching the filter, looking at auctions from id <cursor> on
+        /// first page starts at cursor 0, every next one at next_cursor of the previous page
+        /// the scan stops once the page is full or MAX_PAGE_SCAN auctions were looked at, so a page may hold less
+        /// matching auctions than there are, limit is capped at MAX_PAGE_SIZE, so the response stays within RPC limits
+        #[ink(message)]
+        pub fn get_auctions_page(&self, cursor: u64, limit: u64, filter: AuctionFilter) -> AuctionsPage {
+            let block_timestamp = self.env().block_timestamp();
+            let limit = core::cmp::min(limit, MAX_PAGE_SIZE) as usize;
+            let scan_end = core::cmp::min(self.next_auction_id, cursor.saturating_add(MAX_PAGE_SCAN));
+
+            let mut auctions = Vec::new();
+            let mut auction_id = cursor;
+            while auction_id < scan_end && auctions.len() < limit {
+                if let Some(auction) = self.load_auction(auction_id) {
+                    if Self::matches_filter(&auction, &filter, block_timestamp) {
+                        auctions.push(auction);
+                    }
+                }
+                auction_id += 1;
+            }
+
+            // auctions from the id after the last one looked at are left for the next page
+            let next_cursor = if auction_id < self.next_auction_id { Some(auction_id) } else { None };
+            return AuctionsPage { auctions, next_cursor };
+        }
+
+        /// return at most <limit> bids of the auction's bid log, skipping the first <offset>
+        /// bids are in the order they were placed, limit is capped at MAX_PAGE_SIZE
+        #[ink(message)]
+        pub fn get_bids(&self, auction_id: u64, offset: u64, limit: u64) -> Vec<BidRecord> {
+            let end = core::cmp::min(self.get_bid_count(auction_id), offset.saturating_add(core::cmp::min(limit, MAX_PAGE_SIZE)));
+            return (offset..end).filter_map(|position| self.bid_log.get((auction_id, position))).collect();
+        }
+
+        /// return the number of bids in the auction's bid log
+        #[ink(message)]
+        pub fn get_bid_count(&self, auction_id: u64) -> u64 {
+            return self.bid_log_len.get(auction_id).unwrap_or(0);
+        }
+
+        /// return the auction with given id
+        #[ink(message)]
+        pub fn get_auction(&self, auction_id: u64) -> Option<AuctionInfo> {
+            return self.load_auction(auction_id);
+        }
+
+        /// stop creating auctions and bidding, deadlines of running auctions are frozen until unpause
+        /// cancelling auctions and withdrawing money still works
+        /// only contract owner can call this message
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            if self.env().caller() != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+            if self.paused_since.is_some() {
+                return Err(Error::PausedError);
+            }
+
+            let paused_at = self.env().block_timestamp();
+            self.paused_since = Some(paused_at);
+            self.emit_event(Paused { paused_at });
+            return Ok(());
+        }
+
+        /// allow creating auctions and bidding again
+        /// deadlines of auctions which weren't over when the pause started are moved by the length of the pause
+        /// only contract owner can call this message
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            if self.env().caller() != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+            let paused_since = match self.paused_since {
+                Some(x) => x,
+                None => return Err(Error::NotPausedError)
+            };
+
+            // auctions are moved when they are loaded, see load_auction
+            let block_timestamp = self.env().block_timestamp();
+            self.pauses.insert(self.pause_count, &(paused_since, block_timestamp));
+            self.pause_count += 1;
+            self.paused_since = None;
+
+            self.emit_event(Unpaused { paused_for: block_timestamp - paused_since });
+            return Ok(());
+        }
+
+        /// return whether creating auctions and bidding is paused
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            return self.paused_since.is_some();
+        }
+
+        /// replace the code of this contract, keeping its storage, auctions and money
+        /// after the upgrade migrate_storage has to be called if the new code uses a newer storage version
+        /// only contract owner can call this message
+        /// code_hash -- hash of the new code, which has to be uploaded to the chain already
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            if self.env().caller() != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+
+            if ink_env::set_code_hash(&code_hash).is_err() {
+                return Err(Error::UpgradeError);
+            }
+
+            self.emit_event(CodeUpgraded { code_hash });
+            return Ok(());
+        }
+
+        /// bring the storage written by older code to the layout used by this code
+        /// when the layout changes, a step moving the storage from the older version to the next one is added here
+        /// version 1 is the first layout, so there are no steps yet
+        /// auctions saved in older layouts don't need a step, they are brought up to date when loaded
+        /// only contract owner can call this message
+        /// returns the storage version after the migration
+        #[ink(message)]
+        pub fn migrate_storage(&mut self) -> Result<u32> {
+            if self.env().caller() != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+
+            // storage of this version needs nothing, there is no step for other versions (e.g. written by newer code)
+            let version = self.storage_version();
+            if version != STORAGE_VERSION {
+                return Err(Error::UpgradeError);
+            }
+            return Ok(version);
+        }
+
+        /// return the version of the storage layout, see migrate_storage
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u32 {
+            return self.storage_version();
+        }
+
+        /// return the fee needed to crate an auction
+        #[ink(message)]
+        pub fn get_create_auction_fee(&self) -> u128 {
+            return self.create_auction_fee.clone();
+        }
+
+        /// return the shortest and longest allowed duration of new auctions, in miliseconds
+        #[ink(message)]
+        pub fn get_duration_bounds(&self) -> DurationBounds {
+            return self.duration_bounds();
+        }
+
+        /// set the shortest and longest allowed duration of new auctions, in miliseconds
+        /// min has to be at least 1 milisecond and can't exceed max
+        /// only contract owner can call this message
+        #[ink(message)]
+        pub fn set_duration_bounds(&mut self, min: u64, max: u64) -> Result<()> {
+            if self.env().caller() != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+            if min == 0 || min > max {
+                return Err(Error::InvalidDurationError);
+            }
+
+            ink_env::set_contract_storage_return_size(&Key::from(DURATION_BOUNDS_KEY), &DurationBounds { min, max });
+            self.emit_event(DurationBoundsChanged { min, max });
+            return Ok(());
+        }
+
+        /// return the fee taken from the final price of new auctions, in basis points
+        #[ink(message)]
+        pub fn get_settlement_fee_bps(&self) -> u32 {
+            return self.settlement_fee_bps;
+        }
+
+        /// return the fee taken from the highest bid of new auctions when cancelled, in basis points
+        #[ink(message)]
+        pub fn get_cancellation_fee_bps(&self) -> u32 {
+            return self.cancellation_fee_bps;
+        }
+
+        /// return the fee taken when finalizing particular auction at its highest bid
+        #[ink(message)]
+        pub fn get_finalize_fee_of(&self, auction_id: u64) -> Result<u128> {
+            let auction = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+            return Ok(alleblock_core::fee_of(auction.highest_bid, auction.fee_terms.settlement_fee_bps));
+        }
+
+        /// return how the auction's price would be split if it was settled now, and the cost of cancelling it
+        /// fees are rounded down to the smallest unit of currency, the rest of the price goes to the auction owner
+        #[ink(message)]
+        pub fn quote_fees(&self, auction_id: u64) -> Result<FeeQuote> {
+            let auction = match self.load_auction(auction_id) {
+                Some(x) => x,
+                None => return Err(Error::NoSuchAuctionError)
+            };
+
+            // price the same as finish_auction or buy_now would use
+            let price = match &auction.kind {
+                AuctionKind::Dutch { .. } if auction.auction_state == AuctionState::InProgress => {
+                    Self::dutch_price(&auction, self.price_timestamp())?
+                },
+                _ => alleblock_core::settlement_price(&auction.kind, auction.starting_bid, auction.highest_bid, auction.second_highest_bid)
+            };
+
+            return Ok(auction.fee_terms.quote(price, auction.highest_bid));
+        }
+
+        /// return owner of the contract who receives all the fees
+        #[ink(message)]
+        pub fn get_contract_owner(&self) -> AccountId {
+            return self.contract_owner.clone();
+        }
+
+        /// return the account proposed as the new contract owner
+        #[ink(message)]
+        pub fn get_proposed_owner(&self) -> Option<AccountId> {
+            return self.proposed_owner;
+        }
+
+        /// set the fee for creating an auction
+        /// only contract owner can call this message
+        #[ink(message)]
+        pub fn set_create_auction_fee(&mut self, create_auction_fee: u128) -> Result<()> {
+            if self.env().caller() != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+
+            self.create_auction_fee = create_auction_fee;
+            self.emit_fees_changed();
+            return Ok(());
+        }
+
+        /// set the settlement fee of new auctions, in basis points
+        /// running auctions keep the fee they were created with
+        /// only contract owner can call this message
+        #[ink(message)]
+        pub fn set_settlement_fee_bps(&mut self, settlement_fee_bps: u32) -> Result<()> {
+            if self.env().caller() != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+            if settlement_fee_bps > MAX_FEE_BPS {
+                return Err(Error::InvalidFeeError);
+            }
+
+            self.settlement_fee_bps = settlement_fee_bps;
+            self.emit_fees_changed();
+            return Ok(());
+        }
+
+        /// set the cancellation fee of new auctions, in basis points
+        /// running auctions keep the fee they were created with
+        /// only contract owner can call this message
+        #[ink(message)]
+        pub fn set_cancellation_fee_bps(&mut self, cancellation_fee_bps: u32) -> Result<()> {
+            if self.env().caller() != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+            if cancellation_fee_bps > MAX_FEE_BPS {
+                return Err(Error::InvalidFeeError);
+            }
+
+            self.cancellation_fee_bps = cancellation_fee_bps;
+            self.emit_fees_changed();
+            return Ok(());
+        }
+
+        /// propose a new contract owner, who becomes the owner after calling accept_ownership
+        /// proposing again replaces the previous proposal
+        /// only contract owner can call this message
+        #[ink(message)]
+        pub fn propose_owner(&mut self, proposed_owner: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.contract_owner {
+                return Err(Error::NotAnOwnerError);
+            }
+
+            self.proposed_owner = Some(proposed_owner);
+            self.emit_event(OwnershipProposed { owner: caller, proposed_owner });
+            return Ok(());
+        }
+
+        /// become the contract owner
+        /// only the proposed owner can call this message
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.proposed_owner != Some(caller) {
+                return Err(Error::NotProposedOwnerError);
+            }
+
+            let previous_owner = self.contract_owner;
+            self.contract_owner = caller;
+            self.proposed_owner = None;
+            self.emit_event(OwnershipTransferred { previous_owner, owner: caller });
+            return Ok(());
+        }
+
+        /// get account of its nft storage
+        #[ink(message)]
+        pub fn get_nft_storage(&self) -> AccountId {
+            return self.nft_storage.clone();
+        }
+
+        /// lowest amount which can be bid on an english auction
+        /// the starting bid if nobody has bid yet, otherwise the highest bid raised by the minimal increment
+        fn min_next_bid(auction: &AuctionInfo) -> u128 {
+            return alleblock_core::min_next_bid(auction.starting_bid, auction.highest_bid, &auction.min_increment);
+        }
+
+        /// append a bid to the auction's bid log, unless the log is full
+        /// writes only the new entry and the length, so the cost doesn't depend on the log size
+        fn log_bid(&mut self, auction_id: u64, bidder: AccountId, amount: u128) {
+            let position = self.get_bid_count(auction_id);
+            if position >= MAX_BID_LOG {
+                return;
+            }
+
+            let timestamp = self.env().block_timestamp();
+            self.bid_log.insert((auction_id, position), &BidRecord { bidder, amount, timestamp });
+            self.bid_log_len.insert(auction_id, &(position + 1));
+        }
+
+        /// fees new auctions are created with
+        fn fee_terms(&self) -> FeeTerms {
+            return FeeTerms { settlement_fee_bps: self.settlement_fee_bps, cancellation_fee_bps: self.cancellation_fee_bps };
+        }
+
+        /// let everybody know about the fees of new auctions
+        fn emit_fees_changed(&self) {
+            self.emit_event(FeesChanged {
+                create_auction_fee: self.create_auction_fee,
+                settlement_fee_bps: self.settlement_fee_bps,
+                cancellation_fee_bps: self.cancellation_fee_bps,
+            });
+        }
+
+        /// whether the auction meets every condition of the filter at given time
+        fn matches_filter(auction: &AuctionInfo, filter: &AuctionFilter, timestamp: Timestamp) -> bool {
+            return filter.state.as_ref().map_or(true, |state| *state == auction.auction_state)
+                && filter.owner.map_or(true, |owner| owner == auction.owner)
+                && filter.highest_bidder.map_or(true, |bidder| auction.highest_bid > 0 && bidder == auction.highest_bidder)
+                && filter.nft_contract.map_or(true, |nft_contract| auction.nft_contract_account == Some(nft_contract))
+                && filter.finish_date_passed.map_or(true, |passed| passed == (timestamp > auction.finish_date));
+        }
+
+        /// price for which the item can be bought right away at given time
+        fn buy_now_price(auction: &AuctionInfo, timestamp: Timestamp) -> Result<u128> {
+            return alleblock_core::buy_now_price(
+                &auction.kind,
+                &auction.buy_now,
+                auction.highest_bid,
+                auction.creation_date,
+                auction.finish_date,
+                timestamp
+            ).map_err(Error::from);
+        }
+
+        /// time at which prices are read, prices of dutch auctions don't drop during a pause
+        fn price_timestamp(&self) -> Timestamp {
+            let block_timestamp = self.env().block_timestamp();
+            return self.paused_since.map_or(block_timestamp, |paused_since| core::cmp::min(paused_since, block_timestamp));
+        }
+
+        /// price of a dutch auction at given time
+        fn dutch_price(auction: &AuctionInfo, timestamp: Timestamp) -> Result<u128> {
+            return match auction.kind {
+                AuctionKind::Dutch { start_price, floor_price } => {
+                    Ok(alleblock_core::dutch_price(start_price, floor_price, auction.creation_date, auction.finish_date, timestamp))
+                },
+                _ => Err(Error::WrongAuctionKindError)
+            };
+        }
+
+        /// auction with given id, with deadlines moved by the pauses which ended since it was saved
+        /// creation date of a dutch auction is moved too, see alleblock_core::postpone
+        fn load_auction(&self, auction_id: u64) -> Option<AuctionInfo> {
+            let mut auction = self.auctions.get(auction_id)?.latest();
+
+            for pause_index in auction.pause_index..self.pause_count {
+                let (pause_start, pause_end) = match self.pauses.get(pause_index) {
+                    Some(x) => x,
+                    None => continue
+                };
+
+                // deadlines of closed auctions don't matter anymore
+                if auction.auction_state.is_open() {
+                    alleblock_core::postpone(&mut auction.creation_date, &mut auction.finish_date, &mut auction.kind, pause_start, pause_end - pause_start);
+                }
+            }
+            auction.pause_index = self.pause_count;
+
+            return Some(auction);
+        }
+
+        /// save the auction in the latest layout
+        fn save_auction(&mut self, auction: &AuctionInfo) {
+            self.auctions.insert(auction.id, &VersionedAuctionInfo::V1(auction.clone()));
+        }
+
+        /// run the message holding the reentrancy lock, so contracts it calls can't call back into the contract
+        /// every message moving auctions or money takes the lock
+        fn non_reentrant<T>(&mut self, message: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+            let lock_key = Key::from(REENTRANCY_LOCK_KEY);
+            if ink_env::contract_storage_contains(&lock_key).is_some() {
+                return Err(Error::ReentrancyError);
+            }
+
+            ink_env::set_contract_storage_return_size(&lock_key, &());
+            let result = message(self);
+            ink_env::clear_contract_storage(&lock_key);
+
+            return result;
+        }
+
+        /// duration bounds set by the contract owner, or the defaults
+        fn duration_bounds(&self) -> DurationBounds {
+            return ink_env::get_contract_storage(&Key::from(DURATION_BOUNDS_KEY)).ok().flatten().unwrap_or(DEFAULT_DURATION_BOUNDS);
+        }
+
+        /// version of the storage layout, 0 if it was never written
+        fn storage_version(&self) -> u32 {
+            return ink_env::get_contract_storage(&Key::from(STORAGE_VERSION_KEY)).ok().flatten().unwrap_or(0);
+        }
+
+        /// whether the deadline is over, deadline is frozen if the current pause started before it
+        fn deadline_passed(&self, deadline: Timestamp) -> bool {
+            return alleblock_core::deadline_passed(deadline, self.env().block_timestamp(), self.paused_since);
+        }
+
+        /// give deposits of unrevealed sealed bids to the receiver, or back to the bidders if there is no receiver
+        /// clears all the commitments of the auction
+        fn settle_unrevealed_bids(&mut self, auction_id: u64, receiver: Option<AccountId>) {
+            let bidders = self.sealed_bidders.get(auction_id).unwrap_or_default();
+            for bidder in bidders {
+                if let Some(sealed_bid) = self.sealed_bids.get((auction_id, bidder)) {
+                    if !sealed_bid.revealed {
+                        self.credit(None, receiver.unwrap_or(bidder), sealed_bid.deposit);
+                    }
+                }
+                self.sealed_bids.remove((auction_id, bidder));
+            }
+            self.sealed_bidders.remove(auction_id);
+        }
+
+        /// add money to the account's pending withdrawal balance, in given PSP22 token or in the native currency if None
+        fn credit(&mut self, payment_token: Option<AccountId>, account: AccountId, amount: u128) {
+            if amount == 0 {
+                return;
+            }
+            match payment_token {
+                Some(token) => {
+                    let balance = self.get_pending_token_withdrawal(account, token);
+                    self.pending_token_withdrawals.insert((account, token), &(balance + amount));
+                },
+                None => {
+                    let balance = self.get_pending_withdrawal(account);
+                    self.pending_withdrawals.insert(account, &(balance + amount));
+                }
+            }
+        }
+
+
+        /// send the nft of the auction from the nft storage to the receiver, if the auction has one
+        fn release_nft(&mut self, auction: &AuctionInfo, receiver: AccountId) -> Result<()> {
+            if let (Some(nft_contract), Some(token_id)) = (auction.nft_contract_account, auction.nft_token_id.clone()) {
+                self.transfer_token_by_storage(receiver, nft_contract, token_id.clone())?;
+                self.emit_event(NftReleased { auction_id: auction.id, receiver, nft_contract, token_id });
+            }
+            return Ok(());
+        }
+
+        /// transfer nft to indicated address by nft storage
+        /// failed call is turned into an error instead of trapping the transaction
+        fn transfer_token_by_storage(&mut self, to: AccountId, nft_contract: AccountId, token_id: Id) -> Result<()> {
+            return match Callee::nft_storage_transfer(self.nft_storage, to, nft_contract, token_id) {
+                Ok(Ok(())) => Ok(()),
+                Ok(Err(error)) => Err(Error::NftStorageTransferError(error)),
+                Err(ink_env::Error::Decode(_)) => Err(Error::NftStorageDecodeError),
+                Err(error) => Err(Error::NftStorageCallError(CallFailure::from(error))),
+            };
+        }
+
+        /// emit an event of this contract
+        /// Note: self.env().emit_event can't tell which contract's events are meant when nft_storage is a dependency
+        fn emit_event<E: Into<<Alleblock as ContractEventBase>::Type>>(&self, event: E) {
+            EmitEvent::<Alleblock>::emit_event(self.env(), event);
+        }
+
+
+        /// check if the owner allowed this contract to take the nft
+        fn nft_allowance(&self, nft_contract: AccountId, owner: AccountId, token_id: Id) -> bool {
+            return Callee::nft_allowance(nft_contract, owner, self.env().account_id(), token_id);
+        }
+
+        /// move the nft, which this contract is allowed to take, to the nft storage
+        fn escrow_nft(&self, nft_contract: AccountId, token_id: Id) -> core::result::Result<(), PSP34Error> {
+            return Callee::nft_transfer(nft_contract, self.nft_storage, token_id);
+        }
+
+        /// take <amount> of PSP22 token from the account, which has to approve it to this contract first
+        fn take_token(&self, payment_token: AccountId, from: AccountId, amount: u128) -> core::result::Result<(), PSP22Error> {
+            return Callee::token_transfer_from(payment_token, from, self.env().account_id(), amount);
+        }
+
+        /// give <amount> of PSP22 token owned by this contract to the account
+        fn send_token(&self, payment_token: AccountId, to: AccountId, amount: u128) -> core::result::Result<(), PSP22Error> {
+            return Callee::token_transfer(payment_token, to, amount);
+        }
+    }
+
+    /// calls this contract makes to other contracts
+    /// Note: off-chain tests can't call other contracts, so test builds call the mocks of mocks.rs instead of OnChain
+    pub trait Callees {
+        /// same as PSP34::allowance
+        fn nft_allowance(nft_contract: AccountId, owner: AccountId, operator: AccountId, token_id: Id) -> bool;
+
+        /// same as PSP34::transfer called by this contract
+        fn nft_transfer(nft_contract: AccountId, to: AccountId, token_id: Id) -> core::result::Result<(), PSP34Error>;
+
+        /// NftStorage::transfer called by this contract, the outer error tells why the call itself failed
+        fn nft_storage_transfer(nft_storage: AccountId, to: AccountId, nft_contract: AccountId, token_id: Id) -> core::result::Result<core::result::Result<(), PSP34Error>, ink_env::Error>;
+
+        /// same as PSP22::transfer_from called by this contract
+        fn token_transfer_from(payment_token: AccountId, from: AccountId, to: AccountId, amount: u128) -> core::result::Result<(), PSP22Error>;
+
+        /// same as PSP22::transfer called by this contract
+        fn token_transfer(payment_token: AccountId, to: AccountId, amount: u128) -> core::result::Result<(), PSP22Error>;
+    }
+
+    /// contracts called on-chain, through their typed references
+    #[cfg_attr(test, allow(dead_code))]
+    pub struct OnChain;
+
+    impl Callees for OnChain {
+        fn nft_allowance(nft_contract: AccountId, owner: AccountId, operator: AccountId, token_id: Id) -> bool {
+            return PSP34Ref::allowance(&nft_contract, owner, operator, Some(token_id));
+        }
+
+        fn nft_transfer(nft_contract: AccountId, to: AccountId, token_id: Id) -> core::result::Result<(), PSP34Error> {
+            return PSP34Ref::transfer(&nft_contract, to, token_id, [0x0].to_vec());
+        }
+
+        fn nft_storage_transfer(nft_storage: AccountId, to: AccountId, nft_contract: AccountId, token_id: Id) -> core::result::Result<core::result::Result<(), PSP34Error>, ink_env::Error> {
+            let mut nft_storage: NftStorageRef = FromAccountId::from_account_id(nft_storage);
+            return nft_storage.call_mut().transfer(to, nft_contract, token_id).fire();
+        }
+
+        fn token_transfer_from(payment_token: AccountId, from: AccountId, to: AccountId, amount: u128) -> core::result::Result<(), PSP22Error> {
+            return PSP22Ref::transfer_from(&payment_token, from, to, amount, Vec::new());
+        }
+
+        fn token_transfer(payment_token: AccountId, to: AccountId, amount: u128) -> core::result::Result<(), PSP22Error> {
+            return PSP22Ref::transfer(&payment_token, to, amount, Vec::new());
+        }
+    }
+
+    #[cfg(not(test))]
+    type Callee = OnChain;
+    #[cfg(test)]
+    type Callee = crate::mocks::Mocks;
+
+}
+
+#[cfg(test)]
+mod mocks;
+#[cfg(test)]
+mod tests;
 #[cfg(test)]
-mod tests;
\ No newline at end of file
+mod proptests;
\ No newline at end of file
@@ -0,0 +1,127 @@
+//! mock contracts for off-chain tests, which can't call other contracts
+//! test builds of the contract call them instead of the nft contracts, nft storage and PSP22 tokens, see Callees
+//! nfts are kept in the mock collections of nft storage, see nft_storage/mock_psp34.rs
+use core::cell::RefCell;
+
+use crate::alleblock::{Callees, Id};
+use ink_env::{AccountId, DefaultEnvironment};
+use nft_storage::mock_psp34;
+use openbrush::contracts::traits::psp22::PSP22Error;
+use openbrush::contracts::traits::psp34::PSP34Error;
+
+/// balances of every mock PSP22 token and the planned misbehaviour of the called contracts
+#[derive(Default)]
+struct Contracts {
+    /// (token, account, balance)
+    balances: Vec<(AccountId, AccountId, u128)>,
+    /// error of the next call to nft storage
+    storage_failure: Option<ink_env::Error>,
+    /// error of the next PSP22 transfer
+    token_failure: Option<PSP22Error>,
+    /// code run by the next called contract before it answers, see call_back
+    callback: Option<Box<dyn FnOnce()>>,
+}
+
+thread_local! {
+    static CONTRACTS: RefCell<Contracts> = RefCell::new(Contracts::default());
+}
+
+/// forget all balances, tokens and approvals, tests using the mocks call it first
+pub fn reset() {
+    CONTRACTS.with(|contracts| *contracts.borrow_mut() = Contracts::default());
+    mock_psp34::reset();
+}
+
+/// balance of the account in the PSP22 token
+pub fn token_balance(token: AccountId, account: AccountId) -> u128 {
+    return CONTRACTS.with(|contracts| {
+        contracts.borrow().balances.iter()
+            .find(|(t, a, _)| *t == token && *a == account)
+            .map_or(0, |(_, _, balance)| *balance)
+    });
+}
+
+/// give the account <amount> of the PSP22 token
+pub fn mint_token(token: AccountId, account: AccountId, amount: u128) {
+    set_token_balance(token, account, token_balance(token, account) + amount);
+}
+
+/// make the next call to nft storage fail with the error, before the storage runs
+pub fn fail_next_storage_call(error: ink_env::Error) {
+    CONTRACTS.with(|contracts| contracts.borrow_mut().storage_failure = Some(error));
+}
+
+/// make the next PSP22 transfer fail with the error, before any tokens are moved
+pub fn fail_next_token_transfer(error: PSP22Error) {
+    CONTRACTS.with(|contracts| contracts.borrow_mut().token_failure = Some(error));
+}
+
+/// make the next called contract, which moves nfts or tokens, run the callback before it answers
+/// this is how a hostile contract calls back into the contract while its message is still running
+pub fn call_back(callback: impl FnOnce() + 'static) {
+    CONTRACTS.with(|contracts| contracts.borrow_mut().callback = Some(Box::new(callback)));
+}
+
+/// run the callback set by call_back, if there is one
+fn run_callback() {
+    // taken out first, so the callback can use the mocks too
+    let callback = CONTRACTS.with(|contracts| contracts.borrow_mut().callback.take());
+    if let Some(callback) = callback {
+        callback();
+    }
+}
+
+fn set_token_balance(token: AccountId, account: AccountId, balance: u128) {
+    CONTRACTS.with(|contracts| {
+        let mut contracts = contracts.borrow_mut();
+        contracts.balances.retain(|(t, a, _)| !(*t == token && *a == account));
+        contracts.balances.push((token, account, balance));
+    });
+}
+
+/// same as PSP22::transfer_from, allowances aren't checked
+fn move_token(token: AccountId, from: AccountId, to: AccountId, amount: u128) -> Result<(), PSP22Error> {
+    if let Some(error) = CONTRACTS.with(|contracts| contracts.borrow_mut().token_failure.take()) {
+        return Err(error);
+    }
+    let balance = token_balance(token, from);
+    if balance < amount {
+        return Err(PSP22Error::InsufficientBalance);
+    }
+    set_token_balance(token, from, balance - amount);
+    mint_token(token, to, amount);
+    return Ok(());
+}
+
+/// contracts called by the contract under test, which is the account of the test environment
+pub struct Mocks;
+
+impl Callees for Mocks {
+    fn nft_allowance(nft_contract: AccountId, owner: AccountId, operator: AccountId, token_id: Id) -> bool {
+        return mock_psp34::allowance(nft_contract, owner, operator, Some(token_id));
+    }
+
+    fn nft_transfer(nft_contract: AccountId, to: AccountId, token_id: Id) -> Result<(), PSP34Error> {
+        run_callback();
+        return mock_psp34::transfer(nft_contract, ink_env::account_id::<DefaultEnvironment>(), to, token_id);
+    }
+
+    /// nft storage moves the token it holds, ownership of the storage isn't checked
+    fn nft_storage_transfer(nft_storage: AccountId, to: AccountId, nft_contract: AccountId, token_id: Id) -> Result<Result<(), PSP34Error>, ink_env::Error> {
+        if let Some(error) = CONTRACTS.with(|contracts| contracts.borrow_mut().storage_failure.take()) {
+            return Err(error);
+        }
+        run_callback();
+        return Ok(mock_psp34::transfer(nft_contract, nft_storage, to, token_id));
+    }
+
+    fn token_transfer_from(payment_token: AccountId, from: AccountId, to: AccountId, amount: u128) -> Result<(), PSP22Error> {
+        run_callback();
+        return move_token(payment_token, from, to, amount);
+    }
+
+    fn token_transfer(payment_token: AccountId, to: AccountId, amount: u128) -> Result<(), PSP22Error> {
+        run_callback();
+        return move_token(payment_token, ink_env::account_id::<DefaultEnvironment>(), to, amount);
+    }
+}
@@ -0,0 +1,170 @@
+//! randomized state-machine tests, sequences of calls are checked against invariants after every step
+//! failing sequences are shrunk by proptest to the shortest one which still fails
+use crate::alleblock::{self, *};
+use ink_env::{AccountId, DefaultEnvironment};
+use proptest::prelude::*;
+
+const CREATE_AUCTION_FEE: u128 = 10;
+const SETTLEMENT_FEE_BPS: u32 = 500;
+const CANCELLATION_FEE_BPS: u32 = 300;
+const INITIAL_BALANCE: u128 = 1000;
+
+/// call made by one of the accounts, auction is an index which doesn't have to exist
+#[derive(Debug, Clone)]
+enum Step {
+    Create { caller: usize, starting_bid: u128, duration: u64 },
+    Bid { caller: usize, auction: u64, amount: u128 },
+    Finish { caller: usize, auction: u64 },
+    Cancel { caller: usize, auction: u64, overpaid: u128 },
+    Wait { blocks: u8 },
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    return prop_oneof![
+        (0..4usize, 0..100u128, 1..30u64).prop_map(|(caller, starting_bid, duration)| Step::Create { caller, starting_bid, duration }),
+        (0..4usize, 0..4u64, 0..300u128).prop_map(|(caller, auction, amount)| Step::Bid { caller, auction, amount }),
+        (0..4usize, 0..4u64).prop_map(|(caller, auction)| Step::Finish { caller, auction }),
+        (0..4usize, 0..4u64, 0..5u128).prop_map(|(caller, auction, overpaid)| Step::Cancel { caller, auction, overpaid }),
+        (1..5u8).prop_map(|blocks| Step::Wait { blocks }),
+    ];
+}
+
+/// accounts making the calls, the contract owner only collects fees
+fn callers() -> [AccountId; 4] {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    return [accounts.bob, accounts.charlie, accounts.eve, accounts.frank];
+}
+
+fn balance(account: AccountId) -> u128 {
+    return ink_env::test::get_account_balance::<DefaultEnvironment>(account).unwrap_or(0);
+}
+
+fn move_balance(from: AccountId, to: AccountId, amount: u128) {
+    ink_env::test::set_balance::<DefaultEnvironment>(from, balance(from) - amount);
+    ink_env::test::set_balance::<DefaultEnvironment>(to, balance(to) + amount);
+}
+
+/// call the contract the way the chain does, transferred value is credited before the call and given back if it fails
+/// returns None if the caller can't afford the value
+fn call<T>(
+    contract: &mut Alleblock,
+    caller: AccountId,
+    value: u128,
+    message: impl FnOnce(&mut Alleblock) -> alleblock::Result<T>,
+) -> Option<alleblock::Result<T>> {
+    if balance(caller) < value {
+        return None;
+    }
+    let contract_account = ink_env::account_id::<DefaultEnvironment>();
+    ink_env::test::set_caller::<DefaultEnvironment>(caller);
+    ink_env::test::set_value_transferred::<DefaultEnvironment>(value);
+    move_balance(caller, contract_account, value);
+    let result = message(contract);
+    if result.is_err() {
+        move_balance(contract_account, caller, value);
+    }
+    return Some(result);
+}
+
+/// what the invariants need to remember between the steps
+struct Model {
+    total_balance: u128,
+    owner_fees: u128,
+    auctions: Vec<AuctionInfo>,
+}
+
+fn check_invariants(contract: &Alleblock, model: &mut Model) {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let contract_account = ink_env::account_id::<DefaultEnvironment>();
+    let auctions = contract.get_auctions();
+
+    // money is only moved between the accounts
+    let balances: u128 = callers().iter().map(|account| balance(*account)).sum();
+    assert_eq!(balances + balance(accounts.django) + balance(contract_account), model.total_balance);
+
+    // contract holds exactly what it owes, pending withdrawals and the bids of open auctions
+    let pending: u128 = callers().iter().chain([accounts.django].iter())
+        .map(|account| contract.get_pending_withdrawal(*account))
+        .sum();
+    let escrowed: u128 = auctions.iter()
+        .filter(|auction| auction.auction_state.is_open() && auction.highest_bid > 0)
+        .map(|auction| auction.highest_bidder_max)
+        .sum();
+    assert_eq!(balance(contract_account), pending + escrowed);
+
+    // contract owner gets the creation fees, settlement and cancellation fees
+    assert_eq!(balance(accounts.django) + contract.get_pending_withdrawal(accounts.django), model.owner_fees);
+
+    // finished or cancelled auction never changes, highest bid never decreases
+    for (before, after) in model.auctions.iter().zip(auctions.iter()) {
+        if !before.auction_state.is_open() {
+            assert_eq!(before, after);
+        }
+        assert!(after.highest_bid >= before.highest_bid);
+    }
+    assert!(auctions.len() >= model.auctions.len());
+
+    // nobody leads an auction with a bid of 0, even if it starts at 0
+    for auction in auctions.iter() {
+        assert!(contract.get_bids(auction.id, 0, 100).iter().all(|bid| bid.amount > 0));
+    }
+    model.auctions = auctions;
+}
+
+fn run(steps: Vec<Step>) {
+    ink_env::test::run_test::<DefaultEnvironment, _>(|accounts| {
+        let contract_account = ink_env::account_id::<DefaultEnvironment>();
+        ink_env::test::set_caller::<DefaultEnvironment>(accounts.django);
+        let mut contract = Alleblock::new(CREATE_AUCTION_FEE, SETTLEMENT_FEE_BPS, CANCELLATION_FEE_BPS, accounts.django, AccountId::from([0x0; 32]));
+        for account in callers() {
+            ink_env::test::set_balance::<DefaultEnvironment>(account, INITIAL_BALANCE);
+        }
+        ink_env::test::set_balance::<DefaultEnvironment>(accounts.django, 0);
+        ink_env::test::set_balance::<DefaultEnvironment>(contract_account, 0);
+        let mut model = Model { total_balance: 4 * INITIAL_BALANCE, owner_fees: 0, auctions: Vec::new() };
+
+        for step in steps {
+            match step {
+                Step::Create { caller, starting_bid, duration } => {
+                    let result = call(&mut contract, callers()[caller], CREATE_AUCTION_FEE, |contract| {
+                        contract.create_auction(starting_bid, "auction".to_string(), duration, None, None, None, None, None, None, None)
+                    });
+                    if let Some(Ok(_)) = result {
+                        model.owner_fees += CREATE_AUCTION_FEE;
+                    }
+                },
+                Step::Bid { caller, auction, amount } => {
+                    call(&mut contract, callers()[caller], amount, |contract| contract.bid(auction));
+                },
+                Step::Finish { caller, auction } => {
+                    let highest_bid = contract.get_auction(auction).map_or(0, |auction| auction.highest_bid);
+                    if let Some(Ok(())) = call(&mut contract, callers()[caller], 0, |contract| contract.finish_auction(auction)) {
+                        model.owner_fees += highest_bid * SETTLEMENT_FEE_BPS as u128 / 10_000;
+                    }
+                },
+                Step::Cancel { caller, auction, overpaid } => {
+                    let highest_bid = contract.get_auction(auction).map_or(0, |auction| auction.highest_bid);
+                    let fee = highest_bid * CANCELLATION_FEE_BPS as u128 / 10_000 + overpaid;
+                    if let Some(Ok(())) = call(&mut contract, callers()[caller], fee, |contract| contract.cancel_auction(auction)) {
+                        model.owner_fees += fee;
+                    }
+                },
+                Step::Wait { blocks } => {
+                    for _ in 0..blocks {
+                        ink_env::test::advance_block::<DefaultEnvironment>();
+                    }
+                },
+            }
+            check_invariants(&contract, &mut model);
+        }
+        return Ok(());
+    })
+    .unwrap();
+}
+
+proptest! {
+    #[test]
+    fn auction_invariants_test(steps in prop::collection::vec(step(), 1..40)) {
+        run(steps);
+    }
+}
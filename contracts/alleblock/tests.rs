@@ -1,7 +1,11 @@
 use core::fmt::Debug;
 
 use crate::alleblock::{self, *};
+use crate::mocks;
+use nft_storage::mock_psp34;
 use ink_env::{AccountId, DefaultEnvironment};
+use openbrush::contracts::traits::psp22::PSP22Error;
+use openbrush::contracts::traits::psp34::PSP34Error;
 /// Imports `ink_lang` so we can use `#[ink::test]`.
 use ink_lang as ink;
 
@@ -25,6 +29,14 @@ fn assert_account_balance_equals(account: AccountId, balance: u128) {
     )
 }
 
+type Event = <Alleblock as ink::reflect::ContractEventBase>::Type;
+
+fn recorded_events() -> Vec<Event> {
+    return ink_env::test::recorded_events()
+        .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+        .collect();
+}
+
 fn assert_call_result_eq<T: PartialEq + Debug>(
     call_result: alleblock::Result<T>,
     expected: alleblock::Result<T>,
@@ -37,20 +49,38 @@ fn assert_call_result_eq<T: PartialEq + Debug>(
     }
 }
 
+fn create_nft_auction(contract: &mut Alleblock, collection: AccountId, token: u8) -> alleblock::Result<u64> {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    return contract.create_auction(5, "nft".to_string(), 3, Some(collection), Some(Id::U8(token)), None, None, None, None, None);
+}
+
+/// write the contract struct to the storage, where the chain keeps it between transactions
+fn save_contract(contract: &Alleblock) {
+    ink_storage::traits::push_spread_root(contract, &ink_primitives::Key::from([0x00; 32]));
+}
+
+/// contract as loaded from the storage by the chain, when a transaction or another contract calls it
+fn load_contract() -> Alleblock {
+    return ink_storage::traits::pull_spread_root(&ink_primitives::Key::from([0x00; 32]));
+}
+
 // tests
 
 #[ink::test]
 fn creation_test() {
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
-    let contract = Alleblock::new(1, 2, accounts.django);
+    let contract = Alleblock::new(1, 5000, 5000, accounts.django, AccountId::from([0x0; 32]));
     assert_eq!(contract.get_create_auction_fee(), 1);
-    assert_eq!(contract.get_finalize_fee_interest(), 2);
+    assert_eq!(contract.get_settlement_fee_bps(), 5000);
+    assert_eq!(contract.get_cancellation_fee_bps(), 5000);
     assert_eq!(contract.get_contract_owner(), accounts.django);
 }
 
 #[ink::test]
 fn no_such_auction_test() {
-    let mut contract = Alleblock::new(1, 2, ink_env::account_id::<DefaultEnvironment>());
+    let mut contract = Alleblock::new(1, 5000, 5000, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
     assert_call_result_eq(contract.bid(0), Err(Error::NoSuchAuctionError));
     assert_call_result_eq(contract.finish_auction(0), Err(Error::NoSuchAuctionError));
     assert_call_result_eq(contract.cancel_auction(0), Err(Error::NoSuchAuctionError));
@@ -58,18 +88,18 @@ fn no_such_auction_test() {
 
 #[ink::test]
 fn create_auction_test() {
-    let mut contract = Alleblock::new(1, 2, ink_env::account_id::<DefaultEnvironment>());
+    let mut contract = Alleblock::new(1, 5000, 5000, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
 
     set_caller_and_update_balance(accounts.bob, Some(1000));
     set_value_transferred(1);
 
     assert_call_result_eq(
-        contract.create_auction(5, "auction 1".to_string(), 5),
+        contract.create_auction(5, "auction 1".to_string(), 5, None, None, None, None, None, None, None),
         Ok(0),
     );
     assert_call_result_eq(
-        contract.create_auction(15, "auction 2".to_string(), 3),
+        contract.create_auction(15, "auction 2".to_string(), 3, None, None, None, None, None, None, None),
         Ok(1),
     );
 
@@ -82,9 +112,23 @@ fn create_auction_test() {
             starting_bid: 5,
             highest_bid: 0,
             highest_bidder: caller,
+            highest_bidder_max: 0,
             creation_date: 0,
             finish_date: 5,
             auction_state: AuctionState::InProgress,
+            nft_contract_account: None,
+            nft_token_id: None,
+            soft_close: None,
+            extended_by: 0,
+            kind: AuctionKind::English,
+            second_highest_bid: 0,
+            buy_now: None,
+            min_increment: None,
+            reserve_hash: None,
+            reserve: None,
+            payment_token: None,
+            fee_terms: FeeTerms { settlement_fee_bps: 5000, cancellation_fee_bps: 5000 },
+            pause_index: 0,
         },
         AuctionInfo {
             id: 1,
@@ -93,9 +137,23 @@ fn create_auction_test() {
             starting_bid: 15,
             highest_bid: 0,
             highest_bidder: caller,
+            highest_bidder_max: 0,
             creation_date: 0,
             finish_date: 3,
             auction_state: AuctionState::InProgress,
+            nft_contract_account: None,
+            nft_token_id: None,
+            soft_close: None,
+            extended_by: 0,
+            kind: AuctionKind::English,
+            second_highest_bid: 0,
+            buy_now: None,
+            min_increment: None,
+            reserve_hash: None,
+            reserve: None,
+            payment_token: None,
+            fee_terms: FeeTerms { settlement_fee_bps: 5000, cancellation_fee_bps: 5000 },
+            pause_index: 0,
         },
     ];
     assert_eq!(contract.get_auctions(), expected_auctions)
@@ -106,46 +164,53 @@ fn auction_creation_fee_test() {
     let auction_creation_fee = 10;
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
     set_caller_and_update_balance(accounts.django, Some(0));
-    let mut contract = Alleblock::new(auction_creation_fee, 20, accounts.django);
+    let mut contract = Alleblock::new(auction_creation_fee, 500, 500, accounts.django, AccountId::from([0x0; 32]));
 
     set_caller_and_update_balance(accounts.bob, Some(1000));
 
     set_value_transferred(auction_creation_fee - 1);
     assert_call_result_eq(
-        contract.create_auction(5, "auction 1".to_string(), 3),
+        contract.create_auction(5, "auction 1".to_string(), 3, None, None, None, None, None, None, None),
         Err(Error::TooLowFeeError),
     );
 
     set_value_transferred(auction_creation_fee);
     assert_call_result_eq(
-        contract.create_auction(5, "auction 1".to_string(), 3),
+        contract.create_auction(5, "auction 1".to_string(), 3, None, None, None, None, None, None, None),
         Ok(0),
     );
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), auction_creation_fee);
+    assert_account_balance_equals(accounts.django, 0);
+
+    // fee is credited to the contract owner, who withdraws it
+    set_caller_and_update_balance(accounts.django, None);
+    set_value_transferred(0);
+    assert_call_result_eq(contract.withdraw(), Ok(auction_creation_fee));
     assert_account_balance_equals(accounts.django, auction_creation_fee);
 }
 
 #[ink::test]
 fn bid_state_errors_test() {
-    let mut contract = Alleblock::new(10, 20, ink_env::account_id::<DefaultEnvironment>());
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
 
     set_caller_and_update_balance(accounts.bob, Some(1000));
     set_value_transferred(10);
 
     assert_call_result_eq(
-        contract.create_auction(5, "normal auction".to_string(), 100),
+        contract.create_auction(5, "normal auction".to_string(), 100, None, None, None, None, None, None, None),
         Ok(0),
     );
     assert_call_result_eq(
-        contract.create_auction(5, "finalized auction".to_string(), 3),
+        contract.create_auction(5, "finalized auction".to_string(), 3, None, None, None, None, None, None, None),
         Ok(1),
     );
     assert_call_result_eq(
-        contract.create_auction(5, "cancelled auction".to_string(), 100),
+        contract.create_auction(5, "cancelled auction".to_string(), 100, None, None, None, None, None, None, None),
         Ok(2),
     );
     assert_call_result_eq(
-        contract.create_auction(5, "auction after deadline".to_string(), 3),
+        contract.create_auction(5, "auction after deadline".to_string(), 3, None, None, None, None, None, None, None),
         Ok(3),
     );
     let (normal_id, finalized_id, cancelled_id, after_deadline_id) = (0, 1, 2, 3);
@@ -156,7 +221,7 @@ fn bid_state_errors_test() {
 
     set_caller_and_update_balance(accounts.eve, Some(1000));
     set_value_transferred(500);
-    assert_call_result_eq(contract.bid(normal_id), Ok(()));
+    assert_call_result_eq(contract.bid(normal_id), Ok(100));
     assert_call_result_eq(
         contract.bid(finalized_id),
         Err(Error::AuctionNotInProgressError),
@@ -185,68 +250,73 @@ fn bid_test() {
     let minimum_bid = 5;
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
     set_caller_and_update_balance(accounts.django, Some(0));
-    let mut contract = Alleblock::new(10, 20, accounts.django);
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
 
     set_caller_and_update_balance(accounts.bob, Some(1000));
     set_value_transferred(10);
     assert_call_result_eq(
-        contract.create_auction(minimum_bid, "auction".to_string(), 100),
+        contract.create_auction(minimum_bid, "auction".to_string(), 100, None, None, None, None, None, None, None),
         Ok(0),
     );
 
     set_caller_and_update_balance(accounts.eve, Some(1000));
 
     set_value_transferred(minimum_bid - 1);
-    assert_call_result_eq(contract.bid(0), Err(Error::TooLowBidError));
+    assert_call_result_eq(contract.bid(0), Err(Error::TooLowBidError(minimum_bid)));
     assert_eq!(accounts.bob, contract.get_auctions()[0].highest_bidder);
     assert_eq!(0, contract.get_auctions()[0].highest_bid);
 
     set_value_transferred(minimum_bid);
-    assert_call_result_eq(contract.bid(0), Ok(()));
+    assert_call_result_eq(contract.bid(0), Ok(100));
     assert_eq!(accounts.eve, contract.get_auctions()[0].highest_bidder);
     assert_eq!(minimum_bid, contract.get_auctions()[0].highest_bid);
 
     let eve_best_bid = 500;
     set_value_transferred(eve_best_bid);
-    assert_call_result_eq(contract.bid(0), Ok(()));
+    assert_call_result_eq(contract.bid(0), Ok(100));
     assert_eq!(eve_best_bid, contract.get_auctions()[0].highest_bid);
 
     set_caller_and_update_balance(accounts.charlie, Some(1500));
 
     set_value_transferred(eve_best_bid);
-    assert_call_result_eq(contract.bid(0), Err(Error::TooLowBidError));
+    assert_call_result_eq(contract.bid(0), Err(Error::TooLowBidError(eve_best_bid + 1)));
     assert_eq!(accounts.eve, contract.get_auctions()[0].highest_bidder);
     assert_eq!(eve_best_bid, contract.get_auctions()[0].highest_bid);
 
     set_value_transferred(eve_best_bid + 1);
-    assert_call_result_eq(contract.bid(0), Ok(()));
+    assert_call_result_eq(contract.bid(0), Ok(100));
     assert_eq!(accounts.charlie, contract.get_auctions()[0].highest_bidder);
     assert_eq!(eve_best_bid + 1, contract.get_auctions()[0].highest_bid);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), minimum_bid + eve_best_bid);
+
+    set_caller_and_update_balance(accounts.eve, None);
+    set_value_transferred(0);
+    assert_call_result_eq(contract.withdraw(), Ok(minimum_bid + eve_best_bid));
     assert_account_balance_equals(accounts.eve, 1000);
 }
 
 #[ink::test]
 fn finalize_state_errors_test() {
-    let mut contract = Alleblock::new(10, 20, ink_env::account_id::<DefaultEnvironment>());
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
 
     set_caller_and_update_balance(accounts.bob, Some(1000));
     set_value_transferred(10);
 
     assert_call_result_eq(
-        contract.create_auction(5, "auction before deadline".to_string(), 100),
+        contract.create_auction(5, "auction before deadline".to_string(), 100, None, None, None, None, None, None, None),
         Ok(0),
     );
     assert_call_result_eq(
-        contract.create_auction(5, "cancelled auction".to_string(), 100),
+        contract.create_auction(5, "cancelled auction".to_string(), 100, None, None, None, None, None, None, None),
         Ok(1),
     );
     assert_call_result_eq(
-        contract.create_auction(5, "auction after deadline".to_string(), 3),
+        contract.create_auction(5, "auction after deadline".to_string(), 3, None, None, None, None, None, None, None),
         Ok(2),
     );
     assert_call_result_eq(
-        contract.create_auction(5, "auction after deadline - other finishes".to_string(), 3),
+        contract.create_auction(5, "auction after deadline - other finishes".to_string(), 3, None, None, None, None, None, None, None),
         Ok(3),
     );
     let (before_deadline_id, cancelled_id, after_deadline_id, after_deadline_other_finishes_id) =
@@ -283,16 +353,16 @@ fn finalize_state_errors_test() {
 
 #[ink::test]
 fn finish_behaviour_test() {
-    let finalize_fee = 20;
+    let settlement_fee_bps: u32 = 500;
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
     let creation_cost = 10;
     set_caller_and_update_balance(accounts.django, Some(0));
-    let mut contract = Alleblock::new(creation_cost, finalize_fee, accounts.django);
+    let mut contract = Alleblock::new(creation_cost, settlement_fee_bps, settlement_fee_bps, accounts.django, AccountId::from([0x0; 32]));
 
     set_caller_and_update_balance(accounts.bob, Some(1000 + creation_cost));
     set_value_transferred(creation_cost);
     assert_call_result_eq(
-        contract.create_auction(5, "auction 1".to_string(), 3),
+        contract.create_auction(5, "auction 1".to_string(), 3, None, None, None, None, None, None, None),
         Ok(0),
     );
     let bob_balance_after_contract_creation = 1000;
@@ -300,41 +370,45 @@ fn finish_behaviour_test() {
     let eve_bid = 500;
     set_caller_and_update_balance(accounts.eve, Some(1000));
     set_value_transferred(eve_bid);
-    assert_call_result_eq(contract.bid(0), Ok(()));
+    assert_call_result_eq(contract.bid(0), Ok(3));
 
     set_caller_and_update_balance(accounts.bob, None);
     ink_env::test::advance_block::<DefaultEnvironment>();
     set_value_transferred(0);
     assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_call_result_eq(contract.withdraw(), Ok(eve_bid - eve_bid * settlement_fee_bps as u128 / 10_000));
+
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.withdraw(), Ok(creation_cost + eve_bid * settlement_fee_bps as u128 / 10_000));
 
     assert_account_balance_equals(
         accounts.bob,
-        bob_balance_after_contract_creation + eve_bid - eve_bid / (finalize_fee as u128),
+        bob_balance_after_contract_creation + eve_bid - eve_bid * settlement_fee_bps as u128 / 10_000,
     );
     assert_account_balance_equals(
         accounts.django,
-        creation_cost + eve_bid / (finalize_fee as u128),
+        creation_cost + eve_bid * settlement_fee_bps as u128 / 10_000,
     );
 }
 
 #[ink::test]
 fn cancel_state_errors_test() {
-    let mut contract = Alleblock::new(10, 20, ink_env::account_id::<DefaultEnvironment>());
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
 
     set_caller_and_update_balance(accounts.bob, Some(1000));
     set_value_transferred(10);
 
     assert_call_result_eq(
-        contract.create_auction(5, "auction before deadline".to_string(), 100),
+        contract.create_auction(5, "auction before deadline".to_string(), 100, None, None, None, None, None, None, None),
         Ok(0),
     );
     assert_call_result_eq(
-        contract.create_auction(5, "finished auction".to_string(), 3),
+        contract.create_auction(5, "finished auction".to_string(), 3, None, None, None, None, None, None, None),
         Ok(1),
     );
     assert_call_result_eq(
-        contract.create_auction(5, "auction after deadline".to_string(), 3),
+        contract.create_auction(5, "auction after deadline".to_string(), 3, None, None, None, None, None, None, None),
         Ok(2),
     );
     let (before_deadline_id, finished_id, after_deadline_id) = (0, 1, 2);
@@ -374,18 +448,18 @@ fn cancel_behaviour_test() {
     let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
     set_caller_and_update_balance(accounts.django, Some(0));
     let creation_cost = 10;
-    let mut contract = Alleblock::new(creation_cost, 20, accounts.django);
+    let mut contract = Alleblock::new(creation_cost, 500, 500, accounts.django, AccountId::from([0x0; 32]));
 
     set_caller_and_update_balance(accounts.bob, Some(1000 + creation_cost));
     set_value_transferred(creation_cost);
     assert_call_result_eq(
-        contract.create_auction(5, "auction 1".to_string(), 3),
+        contract.create_auction(5, "auction 1".to_string(), 3, None, None, None, None, None, None, None),
         Ok(0),
     );
 
     set_caller_and_update_balance(accounts.eve, Some(1000));
     set_value_transferred(500);
-    assert_call_result_eq(contract.bid(0), Ok(()));
+    assert_call_result_eq(contract.bid(0), Ok(3));
 
     let fee = 500 / 20;
     assert_eq!(contract.get_finalize_fee_of(0), Ok(fee));
@@ -400,6 +474,1370 @@ fn cancel_behaviour_test() {
 
     set_value_transferred(fee);
     assert_call_result_eq(contract.cancel_auction(0), Ok(()));
+
+    set_value_transferred(0);
+    set_caller_and_update_balance(accounts.eve, None);
+    assert_call_result_eq(contract.withdraw(), Ok(500));
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.withdraw(), Ok(creation_cost + fee));
     assert_account_balance_equals(accounts.eve, 1000);
     assert_account_balance_equals(accounts.django, creation_cost + fee);
 }
+
+#[ink::test]
+fn withdraw_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_auction(5, "auction".to_string(), 100, None, None, None, None, None, None, None),
+        Ok(0),
+    );
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(0);
+    assert_call_result_eq(contract.withdraw(), Err(Error::NothingToWithdrawError));
+
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 0);
+
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    set_value_transferred(200);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100);
+
+    // outbid bidder can bid again without withdrawing first
+    set_caller_and_update_balance(accounts.eve, None);
+    set_value_transferred(300);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100);
+    assert_eq!(contract.get_pending_withdrawal(accounts.charlie), 200);
+
+    set_value_transferred(0);
+    assert_call_result_eq(contract.withdraw(), Ok(100));
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 0);
+    assert_call_result_eq(contract.withdraw(), Err(Error::NothingToWithdrawError));
+    assert_account_balance_equals(accounts.eve, 1000 - 300);
+}
+
+#[ink::test]
+fn soft_close_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let soft_close = SoftClose {
+        window: 10,
+        extension: 8,
+        max_total_extension: 12,
+    };
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_auction(5, "auction".to_string(), 20, None, None, Some(soft_close), None, None, None, None),
+        Ok(0),
+    );
+
+    // bid outside of the window doesn't extend the auction
+    set_caller_and_update_balance(accounts.eve, Some(2000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(0), Ok(20));
+
+    // timestamp 12, bid inside of the window extends the auction
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(200);
+    assert_call_result_eq(contract.bid(0), Ok(28));
+
+    // timestamp 24, extension is capped by max_total_extension
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(300);
+    assert_call_result_eq(contract.bid(0), Ok(32));
+    assert_eq!(contract.get_auctions()[0].extended_by, 12);
+
+    // timestamp 30, cap reached
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(400);
+    assert_call_result_eq(contract.bid(0), Ok(32));
+
+    // timestamp 36, auction is over
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(500);
+    assert_call_result_eq(contract.bid(0), Err(Error::AfterFinishDateError));
+    set_value_transferred(0);
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+}
+
+#[ink::test]
+fn dutch_auction_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_dutch_auction(100, 200, "auction".to_string(), 60, None, None),
+        Err(Error::InvalidPriceError),
+    );
+    assert_call_result_eq(
+        contract.create_dutch_auction(700, 100, "auction".to_string(), 60, None, None),
+        Ok(0),
+    );
+    assert_call_result_eq(
+        contract.create_auction(5, "english auction".to_string(), 60, None, None, None, None, None, None, None),
+        Ok(1),
+    );
+    assert_eq!(contract.get_current_price(0), Ok(700));
+    assert_eq!(contract.get_current_price(1), Err(Error::WrongAuctionKindError));
+    assert_eq!(contract.get_buy_now_price(0), Ok(700));
+
+    // timestamp 12, price fell by 12/60 of the range
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_eq!(contract.get_current_price(0), Ok(580));
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(600);
+    assert_call_result_eq(contract.bid(0), Err(Error::WrongAuctionKindError));
+    assert_call_result_eq(contract.buy_now(1), Err(Error::BuyNowUnavailableError));
+
+    set_value_transferred(579);
+    assert_call_result_eq(contract.buy_now(0), Err(Error::TooLowBidError(580)));
+
+    set_value_transferred(600);
+    assert_call_result_eq(contract.buy_now(0), Ok(()));
+    assert_eq!(contract.get_auctions()[0].auction_state, AuctionState::Finished);
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.eve);
+    assert_eq!(contract.get_auctions()[0].highest_bid, 580);
+
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    assert_call_result_eq(contract.buy_now(0), Err(Error::AuctionNotInProgressError));
+
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 20);
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 580 - 580 / 20);
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), 2 * 10 + 580 / 20);
+}
+
+#[ink::test]
+fn dutch_auction_floor_price_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_dutch_auction(u128::MAX, 100, "auction".to_string(), 12, None, None),
+        Ok(0),
+    );
+
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_eq!(contract.get_current_price(0), Ok(u128::MAX - (u128::MAX - 100) / 2));
+
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_eq!(contract.get_current_price(0), Ok(100));
+
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_eq!(contract.get_current_price(0), Ok(100));
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.buy_now(0), Err(Error::AfterFinishDateError));
+
+    // nobody bought the item
+    set_value_transferred(0);
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_eq!(contract.get_auctions()[0].highest_bid, 0);
+}
+
+fn commit_sealed_bid(contract: &mut Alleblock, bidder: AccountId, auction_id: u64, amount: u128, deposit: u128) {
+    let hash = contract.get_sealed_bid_hash(auction_id, bidder, amount, [7; 32]);
+    set_caller_and_update_balance(bidder, Some(1000));
+    set_value_transferred(deposit);
+    assert_call_result_eq(contract.commit_bid(auction_id, hash), Ok(()));
+}
+
+fn create_sealed_auction(contract: &mut Alleblock, pricing: SealedPricing, unrevealed: UnrevealedDeposit) -> u64 {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    let auction_id = contract
+        .create_sealed_auction(50, "sealed auction".to_string(), 10, 10, pricing, unrevealed, None, None)
+        .unwrap();
+    ink_env::test::transfer_in::<DefaultEnvironment>(10);
+    auction_id
+}
+
+#[ink::test]
+fn sealed_bid_phases_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let auction_id = create_sealed_auction(&mut contract, SealedPricing::FirstPrice, UnrevealedDeposit::Refund);
+    assert_eq!(contract.get_auctions()[0].auction_state, AuctionState::Commit);
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(auction_id), Err(Error::WrongAuctionKindError));
+
+    let hash = contract.get_sealed_bid_hash(auction_id, accounts.eve, 100, [7; 32]);
+    set_value_transferred(49);
+    assert_call_result_eq(contract.commit_bid(auction_id, hash), Err(Error::TooLowBidError(50)));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.commit_bid(auction_id, hash), Ok(()));
+
+    set_value_transferred(0);
+    assert_call_result_eq(contract.reveal_bid(auction_id, 100, [7; 32]), Err(Error::NotRevealPhaseError));
+
+    // timestamp 12, reveal phase
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(100);
+    assert_call_result_eq(contract.commit_bid(auction_id, hash), Err(Error::NotCommitPhaseError));
+
+    set_value_transferred(0);
+    assert_call_result_eq(contract.reveal_bid(auction_id, 101, [7; 32]), Err(Error::InvalidRevealError));
+    assert_call_result_eq(contract.reveal_bid(auction_id, 100, [8; 32]), Err(Error::InvalidRevealError));
+    assert_call_result_eq(contract.reveal_bid(auction_id, 100, [7; 32]), Ok(()));
+    assert_call_result_eq(contract.reveal_bid(auction_id, 100, [7; 32]), Err(Error::NoSuchBidError));
+    assert_eq!(contract.get_auctions()[0].auction_state, AuctionState::Reveal);
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.eve);
+
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    assert_call_result_eq(contract.reveal_bid(auction_id, 100, [7; 32]), Err(Error::NoSuchBidError));
+    assert_call_result_eq(contract.finish_auction(auction_id), Err(Error::BeforeFinishDateError));
+
+    // timestamp 24, auction is over
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.reveal_bid(auction_id, 100, [7; 32]), Err(Error::NotRevealPhaseError));
+    assert_call_result_eq(contract.finish_auction(auction_id), Ok(()));
+    assert_eq!(contract.get_auctions()[0].auction_state, AuctionState::Finished);
+}
+
+#[ink::test]
+fn sealed_bid_first_price_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+    let auction_id = create_sealed_auction(&mut contract, SealedPricing::FirstPrice, UnrevealedDeposit::Forfeit);
+
+    commit_sealed_bid(&mut contract, accounts.eve, auction_id, 300, 400);
+    commit_sealed_bid(&mut contract, accounts.charlie, auction_id, 200, 200);
+    commit_sealed_bid(&mut contract, accounts.frank, auction_id, 500, 500);
+
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(0);
+    set_caller_and_update_balance(accounts.charlie, None);
+    assert_call_result_eq(contract.reveal_bid(auction_id, 200, [7; 32]), Ok(()));
+    set_caller_and_update_balance(accounts.eve, None);
+    assert_call_result_eq(contract.reveal_bid(auction_id, 300, [7; 32]), Ok(()));
+    assert_eq!(contract.get_pending_withdrawal(accounts.charlie), 200);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100);
+
+    // frank never reveals his bid
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(auction_id), Ok(()));
+
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.eve);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100);
+    assert_eq!(contract.get_pending_withdrawal(accounts.frank), 0);
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 500 + 300 - 300 / 20);
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), 10 + 300 / 20);
+    assert_eq!(contract.get_sealed_bid(auction_id, accounts.frank), None);
+}
+
+#[ink::test]
+fn sealed_bid_second_price_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+    let auction_id = create_sealed_auction(&mut contract, SealedPricing::SecondPrice, UnrevealedDeposit::Refund);
+
+    commit_sealed_bid(&mut contract, accounts.eve, auction_id, 300, 300);
+    commit_sealed_bid(&mut contract, accounts.charlie, auction_id, 200, 250);
+    commit_sealed_bid(&mut contract, accounts.frank, auction_id, 500, 500);
+
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(0);
+    set_caller_and_update_balance(accounts.eve, None);
+    assert_call_result_eq(contract.reveal_bid(auction_id, 300, [7; 32]), Ok(()));
+    set_caller_and_update_balance(accounts.charlie, None);
+    assert_call_result_eq(contract.reveal_bid(auction_id, 200, [7; 32]), Ok(()));
+
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(auction_id), Ok(()));
+
+    // eve wins and pays charlie's bid
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.eve);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100);
+    assert_eq!(contract.get_pending_withdrawal(accounts.charlie), 250);
+    assert_eq!(contract.get_pending_withdrawal(accounts.frank), 500);
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 200 - 200 / 20);
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), 10 + 200 / 20);
+}
+
+#[ink::test]
+fn sealed_bid_cancel_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+    let auction_id = create_sealed_auction(&mut contract, SealedPricing::SecondPrice, UnrevealedDeposit::Forfeit);
+
+    commit_sealed_bid(&mut contract, accounts.eve, auction_id, 300, 300);
+    commit_sealed_bid(&mut contract, accounts.charlie, auction_id, 200, 250);
+
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(0);
+    assert_call_result_eq(contract.cancel_auction(auction_id), Ok(()));
+    assert_eq!(contract.get_auctions()[0].auction_state, AuctionState::Cancelled);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 300);
+    assert_eq!(contract.get_pending_withdrawal(accounts.charlie), 250);
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 0);
+}
+
+#[ink::test]
+fn buy_now_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+    let buy_now = BuyNow {
+        price: 500,
+        threshold: 200,
+    };
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_auction(600, "auction".to_string(), 100, None, None, None, Some(buy_now.clone()), None, None, None),
+        Err(Error::InvalidPriceError),
+    );
+    assert_call_result_eq(
+        contract.create_auction(5, "auction".to_string(), 100, None, None, None, Some(buy_now), None, None, None),
+        Ok(0),
+    );
+    assert_eq!(contract.get_buy_now_price(0), Ok(500));
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    set_value_transferred(499);
+    assert_call_result_eq(contract.buy_now(0), Err(Error::TooLowBidError(500)));
+    set_value_transferred(550);
+    assert_call_result_eq(contract.buy_now(0), Ok(()));
+
+    assert_eq!(contract.get_auctions()[0].auction_state, AuctionState::Finished);
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.charlie);
+    assert_eq!(contract.get_auctions()[0].highest_bid, 500);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100);
+    assert_eq!(contract.get_pending_withdrawal(accounts.charlie), 50);
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 500 - 500 / 20);
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), 10 + 500 / 20);
+}
+
+#[ink::test]
+fn buy_now_threshold_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let buy_now = BuyNow {
+        price: 500,
+        threshold: 200,
+    };
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_auction(5, "auction".to_string(), 100, None, None, None, Some(buy_now), None, None, None),
+        Ok(0),
+    );
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(200);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+    assert_eq!(contract.get_buy_now_price(0), Ok(500));
+
+    set_value_transferred(201);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+    assert_eq!(contract.get_buy_now_price(0), Err(Error::BuyNowUnavailableError));
+
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    set_value_transferred(500);
+    assert_call_result_eq(contract.buy_now(0), Err(Error::BuyNowUnavailableError));
+    assert_eq!(contract.get_auctions()[0].auction_state, AuctionState::InProgress);
+}
+
+#[ink::test]
+fn min_increment_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_auction(5, "absolute".to_string(), 100, None, None, None, None, Some(BidIncrement::Absolute(50)), None, None),
+        Ok(0),
+    );
+    assert_call_result_eq(
+        contract.create_auction(5, "basis points".to_string(), 100, None, None, None, None, Some(BidIncrement::BasisPoints(250)), None, None),
+        Ok(1),
+    );
+    assert_eq!(contract.get_min_next_bid(0), Ok(5));
+    assert_eq!(contract.get_min_next_bid(1), Ok(5));
+
+    set_caller_and_update_balance(accounts.eve, Some(2000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+    assert_call_result_eq(contract.bid(1), Ok(100));
+    assert_eq!(contract.get_min_next_bid(0), Ok(150));
+    // 2.5% of 100 is 2.5, rounded up
+    assert_eq!(contract.get_min_next_bid(1), Ok(103));
+
+    set_caller_and_update_balance(accounts.charlie, Some(2000));
+    set_value_transferred(149);
+    assert_call_result_eq(contract.bid(0), Err(Error::TooLowBidError(150)));
+    set_value_transferred(150);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+
+    set_value_transferred(102);
+    assert_call_result_eq(contract.bid(1), Err(Error::TooLowBidError(103)));
+    set_value_transferred(103);
+    assert_call_result_eq(contract.bid(1), Ok(100));
+    assert_eq!(contract.get_min_next_bid(1), Ok(106));
+}
+
+#[ink::test]
+fn zero_bid_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(0, "free".to_string(), 3, None, None, None, None, None, None, None), Ok(0));
+    assert_eq!(contract.get_min_next_bid(0), Ok(1));
+
+    // bid of 0 doesn't make anybody the highest bidder, even if the auction starts at 0
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(0);
+    assert_call_result_eq(contract.bid(0), Err(Error::TooLowBidError(1)));
+    assert_call_result_eq(contract.place_proxy_bid(0), Err(Error::TooLowBidError(1)));
+    assert_eq!(contract.get_bid_count(0), 0);
+
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    match recorded_events().last() {
+        Some(Event::AuctionFinished(event)) => assert_eq!((event.winner, event.price), (None, 0)),
+        _ => panic!("expected AuctionFinished"),
+    }
+}
+
+#[ink::test]
+fn nft_zero_bid_test() {
+    mocks::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let (collection, nft_storage) = (accounts.frank, accounts.charlie);
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, nft_storage);
+    mock_psp34::mint(collection, accounts.bob, Id::U8(1));
+    mock_psp34::approve(collection, accounts.bob, ink_env::account_id::<DefaultEnvironment>(), Some(Id::U8(1)));
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(0, "free nft".to_string(), 3, Some(collection), Some(Id::U8(1)), None, None, None, None, None), Ok(0));
+
+    // nft isn't given away for a bid of 0, it goes back to the owner
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(0);
+    assert_call_result_eq(contract.bid(0), Err(Error::TooLowBidError(1)));
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(1)), Some(accounts.bob));
+}
+
+#[ink::test]
+fn reserve_price_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+    let reserve_hash = contract.get_reserve_hash(300, [3; 32]);
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_auction(5, "reserve not met".to_string(), 3, None, None, None, None, None, Some(reserve_hash), None),
+        Ok(0),
+    );
+    assert_call_result_eq(
+        contract.create_auction(5, "reserve met".to_string(), 3, None, None, None, None, None, Some(reserve_hash), None),
+        Ok(1),
+    );
+    assert_call_result_eq(
+        contract.create_auction(5, "reserve not revealed".to_string(), 3, None, None, None, None, None, Some(reserve_hash), None),
+        Ok(2),
+    );
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(200);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+    assert_call_result_eq(contract.bid(2), Ok(3));
+    set_value_transferred(300);
+    assert_call_result_eq(contract.bid(1), Ok(3));
+
+    set_value_transferred(0);
+    assert_call_result_eq(contract.reveal_reserve(0, 300, [3; 32]), Err(Error::NotAnOwnerError));
+
+    set_caller_and_update_balance(accounts.bob, None);
+    assert_call_result_eq(contract.reveal_reserve(0, 200, [3; 32]), Err(Error::InvalidRevealError));
+    assert_call_result_eq(contract.reveal_reserve(0, 300, [3; 32]), Ok(()));
+    assert_call_result_eq(contract.reveal_reserve(1, 300, [3; 32]), Ok(()));
+    assert_eq!(contract.get_auctions()[0].reserve, Some(300));
+
+    // reserve can't be revealed once bidding is over
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.reveal_reserve(2, 300, [3; 32]), Err(Error::AfterFinishDateError));
+
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_call_result_eq(contract.finish_auction(1), Ok(()));
+    assert_call_result_eq(contract.finish_auction(2), Ok(()));
+    assert_call_result_eq(contract.reveal_reserve(2, 300, [3; 32]), Err(Error::AuctionNotInProgressError));
+
+    // reserve which was never revealed isn't met
+    assert_eq!(contract.get_auctions()[0].auction_state, AuctionState::ReserveNotMet);
+    assert_eq!(contract.get_auctions()[1].auction_state, AuctionState::Finished);
+    assert_eq!(contract.get_auctions()[2].auction_state, AuctionState::ReserveNotMet);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 200 + 200);
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 300 - 300 / 20);
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), 3 * 10 + 300 / 20);
+}
+
+#[ink::test]
+fn proxy_bid_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_auction(5, "proxy".to_string(), 3, None, None, None, None, Some(BidIncrement::Absolute(10)), None, None),
+        Ok(0),
+    );
+
+    // proxy bid starts at the starting bid
+    set_caller_and_update_balance(accounts.eve, Some(2000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.place_proxy_bid(0), Ok(3));
+    assert_eq!(contract.get_auctions()[0].highest_bid, 5);
+    assert_eq!(contract.get_auctions()[0].highest_bidder_max, 100);
+
+    // lower bids are outbid by the proxy bid right away
+    set_caller_and_update_balance(accounts.charlie, Some(2000));
+    set_value_transferred(50);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.eve);
+    assert_eq!(contract.get_auctions()[0].highest_bid, 60);
+    set_value_transferred(95);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.eve);
+    assert_eq!(contract.get_auctions()[0].highest_bid, 100);
+
+    // bid over the maximum takes the lead
+    set_value_transferred(110);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.charlie);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100);
+
+    // new proxy bid only outbids by the minimal increment
+    set_caller_and_update_balance(accounts.eve, None);
+    set_value_transferred(200);
+    assert_call_result_eq(contract.place_proxy_bid(0), Ok(3));
+    assert_eq!(contract.get_auctions()[0].highest_bidder, accounts.eve);
+    assert_eq!(contract.get_auctions()[0].highest_bid, 120);
+
+    // raising own maximum keeps the current bid
+    set_value_transferred(300);
+    assert_call_result_eq(contract.place_proxy_bid(0), Ok(3));
+    assert_eq!(contract.get_auctions()[0].highest_bid, 120);
+    assert_eq!(contract.get_auctions()[0].highest_bidder_max, 300);
+
+    // winner pays the reached bid and gets the rest back
+    set_value_transferred(0);
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100 + 200 + 180);
+    assert_eq!(contract.get_pending_withdrawal(accounts.charlie), 50 + 95 + 110);
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 120 - 120 / 20);
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), 10 + 120 / 20);
+}
+
+#[ink::test]
+fn auctions_page_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    for _ in 0..3 {
+        assert!(contract.create_auction(5, "short".to_string(), 3, None, None, None, None, None, None, None).is_ok());
+    }
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    for _ in 0..2 {
+        assert!(contract.create_auction(5, "long".to_string(), 100, None, None, None, None, None, None, None).is_ok());
+    }
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(50);
+    assert_call_result_eq(contract.bid(1), Ok(3));
+    assert_call_result_eq(contract.bid(4), Ok(100));
+
+    // pages of all the auctions, the scan stops once a page is full
+    let page = contract.get_auctions_page(0, 2, AuctionFilter::default());
+    assert_eq!(page.auctions.iter().map(|auction| auction.id).collect::<Vec<_>>(), vec![0, 1]);
+    assert_eq!(page.next_cursor, Some(2));
+    let page = contract.get_auctions_page(4, 2, AuctionFilter::default());
+    assert_eq!(page.auctions.iter().map(|auction| auction.id).collect::<Vec<_>>(), vec![4]);
+    assert_eq!(page.next_cursor, None);
+    assert_eq!(contract.get_auctions_page(5, 2, AuctionFilter::default()), AuctionsPage { auctions: vec![], next_cursor: None });
+
+    let owned_by_charlie = AuctionFilter { owner: Some(accounts.charlie), ..Default::default() };
+    let page = contract.get_auctions_page(0, 10, owned_by_charlie);
+    assert_eq!(page.auctions.iter().map(|auction| auction.id).collect::<Vec<_>>(), vec![3, 4]);
+    assert_eq!(page.next_cursor, None);
+
+    let bid_by_eve = AuctionFilter { highest_bidder: Some(accounts.eve), ..Default::default() };
+    let page = contract.get_auctions_page(0, 1, bid_by_eve.clone());
+    assert_eq!(page.auctions.iter().map(|auction| auction.id).collect::<Vec<_>>(), vec![1]);
+    assert_eq!(page.next_cursor, Some(2));
+    let page = contract.get_auctions_page(2, 1, bid_by_eve);
+    assert_eq!(page.auctions.iter().map(|auction| auction.id).collect::<Vec<_>>(), vec![4]);
+
+    // owner is the highest bidder only formally when nobody has bid
+    let bid_by_bob = AuctionFilter { highest_bidder: Some(accounts.bob), ..Default::default() };
+    assert_eq!(contract.get_auctions_page(0, 10, bid_by_bob).auctions, vec![]);
+
+    // finish date filter follows the block timestamp
+    set_value_transferred(0);
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    let to_finish = AuctionFilter {
+        state: Some(AuctionState::InProgress),
+        finish_date_passed: Some(true),
+        ..Default::default()
+    };
+    let page = contract.get_auctions_page(0, 10, to_finish);
+    assert_eq!(page.auctions.iter().map(|auction| auction.id).collect::<Vec<_>>(), vec![1, 2]);
+    let live = AuctionFilter { finish_date_passed: Some(false), ..Default::default() };
+    assert_eq!(contract.get_auctions_page(0, 10, live).auctions.len(), 2);
+
+    // a page looks at a bounded number of auctions, also when few of them match
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(10);
+    for _ in 0..1_000 {
+        assert!(contract.create_auction(5, "filler".to_string(), 100, None, None, None, None, None, None, None).is_ok());
+    }
+    let page = contract.get_auctions_page(0, 10, AuctionFilter { owner: Some(accounts.charlie), ..Default::default() });
+    assert_eq!(page.auctions.iter().map(|auction| auction.id).collect::<Vec<_>>(), vec![3, 4]);
+    assert_eq!(page.next_cursor, Some(1_000));
+    let page = contract.get_auctions_page(1_000, 10, AuctionFilter { owner: Some(accounts.charlie), ..Default::default() });
+    assert_eq!(page, AuctionsPage { auctions: vec![], next_cursor: None });
+}
+
+#[ink::test]
+fn events_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(5, "events".to_string(), 3, None, None, None, None, None, None, None), Ok(0));
+    assert_call_result_eq(contract.create_auction(5, "cancelled".to_string(), 3, None, None, None, None, None, None, None), Ok(1));
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.place_proxy_bid(0), Ok(3));
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    set_value_transferred(50);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(0);
+    assert_call_result_eq(contract.cancel_auction(1), Ok(()));
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+
+    let events = recorded_events();
+    assert_eq!(events.len(), 7);
+    match &events[0] {
+        Event::AuctionCreated(event) => {
+            assert_eq!((event.auction_id, event.owner, event.starting_bid, event.finish_date), (0, accounts.bob, 5, 3));
+        },
+        _ => panic!("expected AuctionCreated"),
+    }
+    match &events[2] {
+        Event::BidPlaced(event) => assert_eq!((event.bidder, event.amount), (accounts.eve, 5)),
+        _ => panic!("expected BidPlaced"),
+    }
+    match &events[3] {
+        Event::BidPlaced(event) => assert_eq!((event.bidder, event.amount), (accounts.charlie, 50)),
+        _ => panic!("expected BidPlaced"),
+    }
+    match &events[4] {
+        Event::Outbid(event) => assert_eq!((event.auction_id, event.bidder, event.refund), (0, accounts.charlie, 50)),
+        _ => panic!("expected Outbid"),
+    }
+    match &events[5] {
+        Event::AuctionCancelled(event) => assert_eq!((event.auction_id, event.owner, event.fee), (1, accounts.bob, 0)),
+        _ => panic!("expected AuctionCancelled"),
+    }
+    match &events[6] {
+        Event::AuctionFinished(event) => {
+            assert_eq!((event.auction_id, event.winner, event.price, event.fee), (0, Some(accounts.eve), 51, 51 / 20));
+        },
+        _ => panic!("expected AuctionFinished"),
+    }
+}
+
+#[ink::test]
+fn bid_log_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(5, "logged".to_string(), 100, None, None, None, None, None, None, None), Ok(0));
+    assert_call_result_eq(contract.create_auction(5, "other".to_string(), 100, None, None, None, None, None, None, None), Ok(1));
+    assert_eq!(contract.get_bids(0, 0, 10), vec![]);
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    set_value_transferred(20);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+    set_value_transferred(30);
+    assert_call_result_eq(contract.bid(1), Ok(100));
+    set_caller_and_update_balance(accounts.eve, None);
+    set_value_transferred(40);
+    assert_call_result_eq(contract.bid(0), Ok(100));
+
+    assert_eq!(contract.get_bid_count(0), 3);
+    assert_eq!(contract.get_bid_count(1), 1);
+    assert_eq!(
+        contract.get_bids(0, 0, 2),
+        vec![
+            BidRecord { bidder: accounts.eve, amount: 10, timestamp: 0 },
+            BidRecord { bidder: accounts.charlie, amount: 20, timestamp: 6 },
+        ]
+    );
+    assert_eq!(contract.get_bids(0, 2, 10), vec![BidRecord { bidder: accounts.eve, amount: 40, timestamp: 6 }]);
+    assert_eq!(contract.get_bids(0, 3, 10), vec![]);
+    assert_eq!(contract.get_bids(2, 0, 10), vec![]);
+}
+
+#[ink::test]
+fn token_auction_currency_test() {
+    let mut contract = Alleblock::new(10, 500, 500, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let token = accounts.frank;
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(5, "token".to_string(), 100, None, None, None, None, None, None, Some(token)), Ok(0));
+    assert_call_result_eq(contract.create_auction(5, "native".to_string(), 100, None, None, None, None, None, None, None), Ok(1));
+    assert_eq!(contract.get_auction(0).unwrap().payment_token, Some(token));
+
+    // bids have to be paid in the auction's currency
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(50);
+    assert_call_result_eq(contract.bid(0), Err(Error::WrongCurrencyError));
+    assert_call_result_eq(contract.place_proxy_bid(0), Err(Error::WrongCurrencyError));
+    set_value_transferred(0);
+    assert_call_result_eq(contract.bid_in_token(1, 50), Err(Error::WrongCurrencyError));
+    assert_call_result_eq(contract.place_proxy_bid_in_token(1, 50), Err(Error::WrongCurrencyError));
+
+    assert_eq!(contract.get_pending_token_withdrawal(accounts.eve, token), 0);
+    assert_call_result_eq(contract.withdraw_token(token), Err(Error::NothingToWithdrawError));
+
+    // fee of a token auction can't be paid in the native currency
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(10);
+    assert_call_result_eq(contract.cancel_auction(0), Err(Error::WrongCurrencyError));
+    set_value_transferred(0);
+    assert_call_result_eq(contract.cancel_auction(0), Ok(()));
+}
+
+#[ink::test]
+fn token_payment_test() {
+    mocks::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let token = accounts.frank;
+    let contract_account = ink_env::account_id::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(5, "token".to_string(), 3, None, None, None, None, None, None, Some(token)), Ok(0));
+
+    // bid is taken from the bidder's token balance, hostile token can't call back while it's taken
+    mocks::mint_token(token, accounts.eve, 150);
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(0);
+    save_contract(&contract);
+    let reentered = std::rc::Rc::new(core::cell::RefCell::new(None));
+    let result = reentered.clone();
+    mocks::call_back(move || *result.borrow_mut() = Some(load_contract().withdraw_token(token)));
+    assert_call_result_eq(contract.bid_in_token(0, 100), Ok(3));
+    assert_eq!(reentered.borrow_mut().take(), Some(Err(Error::ReentrancyError)));
+    assert_eq!((mocks::token_balance(token, accounts.eve), mocks::token_balance(token, contract_account)), (50, 100));
+
+    // seller and contract owner withdraw their parts in the token
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    set_caller_and_update_balance(accounts.bob, None);
+
+    // failed transfer keeps the money waiting for the seller
+    mocks::fail_next_token_transfer(PSP22Error::Custom("frozen".to_string()));
+    assert_call_result_eq(contract.withdraw_token(token), Err(Error::TokenTransferError(PSP22Error::Custom("frozen".to_string()))));
+    assert_eq!(contract.get_pending_token_withdrawal(accounts.bob, token), 95);
+    assert_eq!(mocks::token_balance(token, contract_account), 100);
+
+    assert_call_result_eq(contract.withdraw_token(token), Ok(95));
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.withdraw_token(token), Ok(5));
+    assert_eq!((mocks::token_balance(token, accounts.bob), mocks::token_balance(token, accounts.django)), (95, 5));
+    assert_eq!(mocks::token_balance(token, contract_account), 0);
+
+    // error of the token is passed to the caller
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(5, "unpaid".to_string(), 3, None, None, None, None, None, None, Some(token)), Ok(1));
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    set_value_transferred(0);
+    assert_call_result_eq(contract.bid_in_token(1, 100), Err(Error::TokenTransferError(PSP22Error::InsufficientBalance)));
+}
+
+#[ink::test]
+fn basis_point_fees_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    // 2.5% of the price when settled, 10% of the highest bid when cancelled
+    let mut contract = Alleblock::new(10, 250, 1000, accounts.django, AccountId::from([0x0; 32]));
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(5, "settled".to_string(), 3, None, None, None, None, None, None, None), Ok(0));
+    assert_call_result_eq(contract.create_auction(5, "cancelled".to_string(), 3, None, None, None, None, None, None, None), Ok(1));
+    assert_eq!(
+        contract.quote_fees(0),
+        Ok(FeeQuote { price: 0, seller_amount: 0, platform_fee: 0, cancellation_cost: 0 })
+    );
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(199);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+    assert_call_result_eq(contract.bid(1), Ok(3));
+
+    // fees are rounded down
+    assert_eq!(
+        contract.quote_fees(0),
+        Ok(FeeQuote { price: 199, seller_amount: 195, platform_fee: 4, cancellation_cost: 19 })
+    );
+    assert_eq!(contract.quote_fees(2), Err(Error::NoSuchAuctionError));
+
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(18);
+    assert_call_result_eq(contract.cancel_auction(1), Err(Error::TooLowFeeError));
+    set_value_transferred(19);
+    assert_call_result_eq(contract.cancel_auction(1), Ok(()));
+
+    set_value_transferred(0);
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 195);
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), 2 * 10 + 4 + 19);
+}
+
+#[ink::test]
+#[should_panic]
+fn fee_over_whole_amount_test() {
+    Alleblock::new(10, 10_001, 0, ink_env::account_id::<DefaultEnvironment>(), AccountId::from([0x0; 32]));
+}
+
+#[ink::test]
+fn admin_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    set_value_transferred(10);
+    assert_call_result_eq(contract.create_auction(5, "old fees".to_string(), 3, None, None, None, None, None, None, None), Ok(0));
+
+    // only contract owner changes the fees
+    set_value_transferred(0);
+    assert_call_result_eq(contract.set_create_auction_fee(20), Err(Error::NotAnOwnerError));
+    assert_call_result_eq(contract.set_settlement_fee_bps(1000), Err(Error::NotAnOwnerError));
+    assert_call_result_eq(contract.set_cancellation_fee_bps(1000), Err(Error::NotAnOwnerError));
+
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.set_create_auction_fee(20), Ok(()));
+    assert_call_result_eq(contract.set_settlement_fee_bps(10_001), Err(Error::InvalidFeeError));
+    assert_call_result_eq(contract.set_settlement_fee_bps(1000), Ok(()));
+    assert_call_result_eq(contract.set_cancellation_fee_bps(2000), Ok(()));
+    assert_eq!(contract.get_create_auction_fee(), 20);
+    assert_eq!(contract.get_settlement_fee_bps(), 1000);
+    assert_eq!(contract.get_cancellation_fee_bps(), 2000);
+
+    // new auctions use new fees, running ones keep the old ones
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(10);
+    assert_call_result_eq(
+        contract.create_auction(5, "new fees".to_string(), 3, None, None, None, None, None, None, None),
+        Err(Error::TooLowFeeError),
+    );
+    set_value_transferred(20);
+    assert_call_result_eq(contract.create_auction(5, "new fees".to_string(), 3, None, None, None, None, None, None, None), Ok(1));
+    assert_eq!(contract.get_auction(0).unwrap().fee_terms, FeeTerms { settlement_fee_bps: 500, cancellation_fee_bps: 500 });
+    assert_eq!(contract.get_auction(1).unwrap().fee_terms, FeeTerms { settlement_fee_bps: 1000, cancellation_fee_bps: 2000 });
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+    assert_call_result_eq(contract.bid(1), Ok(3));
+    assert_eq!(contract.quote_fees(0).unwrap().platform_fee, 5);
+    assert_eq!(contract.quote_fees(1).unwrap().platform_fee, 10);
+    assert_eq!(contract.quote_fees(1).unwrap().cancellation_cost, 20);
+
+    // ownership is transferred in two steps
+    set_value_transferred(0);
+    assert_call_result_eq(contract.propose_owner(accounts.eve), Err(Error::NotAnOwnerError));
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.propose_owner(accounts.eve), Ok(()));
+    assert_eq!(contract.get_proposed_owner(), Some(accounts.eve));
+    assert_eq!(contract.get_contract_owner(), accounts.django);
+
+    set_caller_and_update_balance(accounts.charlie, None);
+    assert_call_result_eq(contract.accept_ownership(), Err(Error::NotProposedOwnerError));
+    set_caller_and_update_balance(accounts.eve, None);
+    assert_call_result_eq(contract.accept_ownership(), Ok(()));
+    assert_eq!(contract.get_contract_owner(), accounts.eve);
+    assert_eq!(contract.get_proposed_owner(), None);
+    assert_call_result_eq(contract.accept_ownership(), Err(Error::NotProposedOwnerError));
+
+    // fees go to the new owner
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 5);
+
+    let events = recorded_events();
+    let fees_changed = events.iter().filter(|event| matches!(event, Event::FeesChanged(_))).count();
+    assert_eq!(fees_changed, 3);
+    match &events[events.len() - 2] {
+        Event::OwnershipTransferred(event) => assert_eq!((event.previous_owner, event.owner), (accounts.django, accounts.eve)),
+        _ => panic!("expected OwnershipTransferred"),
+    }
+}
+
+#[ink::test]
+fn pause_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(0, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    assert_call_result_eq(contract.create_auction(5, "running".to_string(), 10, None, None, None, None, None, None, None), Ok(0));
+    assert_call_result_eq(contract.create_auction(5, "over".to_string(), 3, None, None, None, None, None, None, None), Ok(1));
+    assert_call_result_eq(contract.create_auction(5, "cancelled".to_string(), 10, None, None, None, None, None, None, None), Ok(2));
+    assert_call_result_eq(contract.create_dutch_auction(120, 0, "dutch".to_string(), 12, None, None), Ok(3));
+
+    set_caller_and_update_balance(accounts.charlie, Some(1000));
+    set_value_transferred(50);
+    assert_call_result_eq(contract.bid(0), Ok(10));
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(0), Ok(10));
+
+    // only contract owner pauses
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(0);
+    assert_call_result_eq(contract.pause(), Err(Error::NotAnOwnerError));
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.unpause(), Err(Error::NotPausedError));
+    assert_call_result_eq(contract.pause(), Ok(()));
+    assert_call_result_eq(contract.pause(), Err(Error::PausedError));
+    assert!(contract.is_paused());
+
+    // no new auctions and bids
+    set_caller_and_update_balance(accounts.bob, None);
+    assert_call_result_eq(
+        contract.create_auction(5, "paused".to_string(), 10, None, None, None, None, None, None, None),
+        Err(Error::PausedError),
+    );
+    set_caller_and_update_balance(accounts.charlie, None);
+    set_value_transferred(200);
+    assert_call_result_eq(contract.bid(0), Err(Error::PausedError));
+
+    // running auction can't be finished, the one over before the pause can
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(0);
+    assert_call_result_eq(contract.finish_auction(0), Err(Error::BeforeFinishDateError));
+    assert_call_result_eq(contract.finish_auction(1), Ok(()));
+
+    // dutch price stays where it was when the pause started
+    assert_eq!(contract.get_current_price(3), Ok(60));
+
+    // cancelling and withdrawing still work
+    assert_call_result_eq(contract.withdraw(), Ok(50));
+    set_caller_and_update_balance(accounts.bob, None);
+    assert_call_result_eq(contract.cancel_auction(2), Ok(()));
+
+    // deadline is moved by the length of the pause
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.unpause(), Ok(()));
+    assert!(!contract.is_paused());
+    assert_eq!(contract.get_auction(0).unwrap().finish_date, 22);
+    assert_eq!(contract.get_auction(1).unwrap().finish_date, 3);
+
+    // dutch price curve continues from the price at the pause start
+    let dutch = contract.get_auction(3).unwrap();
+    assert_eq!((dutch.creation_date, dutch.finish_date), (12, 24));
+    assert_eq!(contract.get_current_price(3), Ok(60));
+    assert_call_result_eq(contract.finish_auction(0), Err(Error::BeforeFinishDateError));
+
+    set_caller_and_update_balance(accounts.charlie, None);
+    set_value_transferred(200);
+    assert_call_result_eq(contract.bid(0), Ok(22));
+
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_value_transferred(0);
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_eq!(contract.get_auction(0).unwrap().highest_bidder, accounts.charlie);
+
+    let events = recorded_events();
+    assert!(events.iter().any(|event| matches!(event, Event::Paused(event) if event.paused_at == 6)));
+    assert!(events.iter().any(|event| matches!(event, Event::Unpaused(event) if event.paused_for == 12)));
+}
+
+#[ink::test]
+fn upgrade_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(0, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+    assert_eq!(contract.get_storage_version(), 1);
+
+    // only contract owner replaces the code and migrates the storage
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    assert_call_result_eq(contract.set_code([0x01; 32]), Err(Error::NotAnOwnerError));
+    assert_call_result_eq(contract.migrate_storage(), Err(Error::NotAnOwnerError));
+
+    // migrating the current storage changes nothing
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.migrate_storage(), Ok(1));
+    assert_eq!(contract.get_storage_version(), 1);
+
+    // auctions are saved tagged with the layout version
+    set_caller_and_update_balance(accounts.bob, None);
+    assert_call_result_eq(contract.create_auction(5, "saved".to_string(), 10, None, None, None, None, None, None, None), Ok(0));
+    let auction = contract.get_auction(0).unwrap();
+    let encoded = scale::Encode::encode(&VersionedAuctionInfo::V1(auction.clone()));
+    assert_eq!(encoded[0], 1);
+    match <VersionedAuctionInfo as scale::Decode>::decode(&mut &encoded[..]).unwrap() {
+        VersionedAuctionInfo::V1(decoded) => assert_eq!(decoded, auction),
+    }
+
+    // reloaded storage keeps the version and the auctions
+    save_contract(&contract);
+    let mut reloaded = load_contract();
+    assert_eq!(reloaded.get_storage_version(), 1);
+    assert_eq!(reloaded.get_auction(0), Some(auction));
+
+    // there is no step for storage written by newer code
+    ink_env::set_contract_storage(&ink_primitives::Key::from([0xff; 32]), &2u32);
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(reloaded.migrate_storage(), Err(Error::UpgradeError));
+    assert_eq!(reloaded.get_storage_version(), 2);
+}
+
+#[ink::test]
+fn reentrancy_test() {
+    mocks::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let (collection, nft_storage) = (accounts.frank, accounts.charlie);
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, nft_storage);
+    mock_psp34::mint(collection, accounts.bob, Id::U8(1));
+    mock_psp34::mint(collection, accounts.bob, Id::U8(2));
+    mock_psp34::approve(collection, accounts.bob, ink_env::account_id::<DefaultEnvironment>(), None);
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 1), Ok(0));
+    assert_call_result_eq(contract.create_auction(5, "cancelled".to_string(), 10, Some(collection), Some(Id::U8(2)), None, None, None, None, None), Ok(1));
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+    assert_call_result_eq(contract.bid(1), Ok(10));
+    save_contract(&contract);
+
+    // hostile nft storage calls back while the finish releases the token
+    let reentered = std::rc::Rc::new(core::cell::RefCell::new(Vec::new()));
+    let results = reentered.clone();
+    mocks::call_back(move || {
+        set_value_transferred(0);
+        set_caller_and_update_balance(nft_storage, None);
+        let mut contract = load_contract();
+        results.borrow_mut().push(contract.finish_auction(0));
+        results.borrow_mut().push(contract.withdraw().map(|_| ()));
+        set_caller_and_update_balance(accounts.bob, None);
+        set_value_transferred(5);
+        results.borrow_mut().push(contract.cancel_auction(1));
+    });
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(0);
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_eq!(reentered.borrow_mut().split_off(0), vec![Err(Error::ReentrancyError), Err(Error::ReentrancyError), Err(Error::ReentrancyError)]);
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(1)), Some(accounts.eve));
+    assert_eq!(contract.get_auction(1).unwrap().auction_state, AuctionState::InProgress);
+
+    // hostile nft storage calls back while the cancel gives the token back
+    let results = reentered.clone();
+    mocks::call_back(move || {
+        set_value_transferred(0);
+        set_caller_and_update_balance(accounts.bob, None);
+        let mut contract = load_contract();
+        results.borrow_mut().push(contract.withdraw().map(|_| ()));
+        set_value_transferred(5);
+        results.borrow_mut().push(contract.cancel_auction(1));
+    });
+    set_value_transferred(5);
+    assert_call_result_eq(contract.cancel_auction(1), Ok(()));
+    assert_eq!(reentered.borrow_mut().split_off(0), vec![Err(Error::ReentrancyError), Err(Error::ReentrancyError)]);
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(2)), Some(accounts.bob));
+
+    // every auction is settled only once
+    set_value_transferred(0);
+    assert_call_result_eq(contract.finish_auction(0), Err(Error::AuctionNotInProgressError));
+    set_value_transferred(5);
+    assert_call_result_eq(contract.cancel_auction(1), Err(Error::AuctionNotInProgressError));
+    assert_eq!(contract.get_pending_withdrawal(accounts.bob), 95);
+    assert_eq!(contract.get_pending_withdrawal(accounts.eve), 100);
+    assert_eq!(contract.get_pending_withdrawal(accounts.django), 2 * 10 + 10);
+
+    // lock is released after every message, also the failed ones
+    set_value_transferred(0);
+    assert_call_result_eq(contract.withdraw(), Ok(95));
+    assert!(ink_env::contract_storage_contains(&ink_primitives::Key::from([0xfe; 32])).is_none());
+}
+
+#[ink::test]
+fn duration_bounds_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.django, Some(0));
+    let mut contract = Alleblock::new(0, 500, 500, accounts.django, AccountId::from([0x0; 32]));
+    assert_eq!(contract.get_duration_bounds(), DurationBounds { min: 1, max: 365 * 24 * 60 * 60 * 1000 });
+
+    // auctions which end right away or never are rejected
+    set_caller_and_update_balance(accounts.bob, Some(1000));
+    assert_call_result_eq(
+        contract.create_auction(5, "no time".to_string(), 0, None, None, None, None, None, None, None),
+        Err(Error::InvalidDurationError),
+    );
+    assert_call_result_eq(
+        contract.create_auction(5, "forever".to_string(), u64::MAX, None, None, None, None, None, None, None),
+        Err(Error::InvalidDurationError),
+    );
+    assert_call_result_eq(
+        contract.create_sealed_auction(5, "no commit phase".to_string(), 0, 10, SealedPricing::FirstPrice, UnrevealedDeposit::Refund, None, None),
+        Err(Error::InvalidDurationError),
+    );
+    assert_call_result_eq(
+        contract.create_sealed_auction(5, "overflow".to_string(), u64::MAX, 10, SealedPricing::FirstPrice, UnrevealedDeposit::Refund, None, None),
+        Err(Error::InvalidDurationError),
+    );
+
+    // soft close can't push the finish date past the longest duration either
+    let longest = contract.get_duration_bounds().max;
+    let soft_close = SoftClose { window: 5, extension: longest, max_total_extension: longest - 9 };
+    assert_call_result_eq(
+        contract.create_auction(5, "extended forever".to_string(), 10, None, None, Some(soft_close), None, None, None, None),
+        Err(Error::InvalidDurationError),
+    );
+    let soft_close = SoftClose { window: 5, extension: longest, max_total_extension: longest - 10 };
+    assert_call_result_eq(
+        contract.create_auction(5, "extended to the limit".to_string(), 10, None, None, Some(soft_close), None, None, None, None),
+        Ok(0),
+    );
+
+    // only contract owner changes the bounds
+    assert_call_result_eq(contract.set_duration_bounds(5, u64::MAX), Err(Error::NotAnOwnerError));
+    set_caller_and_update_balance(accounts.django, None);
+    assert_call_result_eq(contract.set_duration_bounds(0, 10), Err(Error::InvalidDurationError));
+    assert_call_result_eq(contract.set_duration_bounds(10, 5), Err(Error::InvalidDurationError));
+    assert_call_result_eq(contract.set_duration_bounds(5, u64::MAX), Ok(()));
+    assert_eq!(contract.get_duration_bounds(), DurationBounds { min: 5, max: u64::MAX });
+
+    // finish date can't overflow, also when extended by soft close
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    set_caller_and_update_balance(accounts.bob, None);
+    assert_call_result_eq(
+        contract.create_auction(5, "too short".to_string(), 4, None, None, None, None, None, None, None),
+        Err(Error::InvalidDurationError),
+    );
+    assert_call_result_eq(
+        contract.create_auction(5, "overflow".to_string(), u64::MAX, None, None, None, None, None, None, None),
+        Err(Error::InvalidDurationError),
+    );
+    let soft_close = SoftClose { window: 5, extension: 5, max_total_extension: u64::MAX };
+    assert_call_result_eq(
+        contract.create_auction(5, "endless".to_string(), 10, None, None, Some(soft_close), None, None, None, None),
+        Err(Error::InvalidDurationError),
+    );
+    assert_call_result_eq(contract.create_auction(5, "bounded".to_string(), 10, None, None, None, None, None, None, None), Ok(1));
+    assert_eq!(contract.get_auction(1).unwrap().finish_date, 16);
+}
+
+#[ink::test]
+fn nft_allowance_test() {
+    mocks::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let (collection, nft_storage) = (accounts.frank, accounts.charlie);
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, nft_storage);
+    let contract_account = ink_env::account_id::<DefaultEnvironment>();
+    mock_psp34::mint(collection, accounts.bob, Id::U8(1));
+
+    // contract has to be allowed to take the token
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 1), Err(Error::NoNftAllowanceError));
+
+    // approval of another token or for another account doesn't count
+    mock_psp34::approve(collection, accounts.bob, contract_account, Some(Id::U8(2)));
+    mock_psp34::approve(collection, accounts.bob, accounts.eve, Some(Id::U8(1)));
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 1), Err(Error::NoNftAllowanceError));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(1)), Some(accounts.bob));
+
+    // approved token is escrowed in the nft storage
+    mock_psp34::approve(collection, accounts.bob, contract_account, Some(Id::U8(1)));
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 1), Ok(0));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(1)), Some(nft_storage));
+    let auction = contract.get_auction(0).unwrap();
+    assert_eq!((auction.nft_contract_account, auction.nft_token_id), (Some(collection), Some(Id::U8(1))));
+    match recorded_events().last() {
+        Some(Event::NftEscrowed(event)) => {
+            assert_eq!((event.auction_id, event.owner, event.nft_contract, event.token_id.clone()), (0, accounts.bob, collection, Id::U8(1)));
+        },
+        _ => panic!("expected NftEscrowed"),
+    }
+}
+
+#[ink::test]
+fn nft_transfer_error_test() {
+    mocks::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let (collection, nft_storage) = (accounts.frank, accounts.charlie);
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, nft_storage);
+    let contract_account = ink_env::account_id::<DefaultEnvironment>();
+
+    // approval of all tokens passes the allowance check, but the transfer fails
+    // Note: on-chain the failed transaction reverts the created auction, off-chain it's kept
+    mock_psp34::approve(collection, accounts.bob, contract_account, None);
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 1), Err(Error::NftTransferError));
+
+    // token of somebody else can't be auctioned
+    mock_psp34::mint(collection, accounts.eve, Id::U8(2));
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 2), Err(Error::NftTransferError));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(2)), Some(accounts.eve));
+}
+
+#[ink::test]
+fn nft_finish_test() {
+    mocks::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let (collection, nft_storage) = (accounts.frank, accounts.charlie);
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, nft_storage);
+    let contract_account = ink_env::account_id::<DefaultEnvironment>();
+    mock_psp34::mint(collection, accounts.bob, Id::U8(1));
+    mock_psp34::mint(collection, accounts.bob, Id::U8(2));
+    mock_psp34::approve(collection, accounts.bob, contract_account, None);
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 1), Ok(0));
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 2), Ok(1));
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(50);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+
+    // winner receives the token, token without bids goes back to the owner
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(0);
+    ink_env::test::advance_block::<DefaultEnvironment>();
+    assert_call_result_eq(contract.finish_auction(0), Ok(()));
+    assert_call_result_eq(contract.finish_auction(1), Ok(()));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(1)), Some(accounts.eve));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(2)), Some(accounts.bob));
+
+    let events = recorded_events();
+    let released: Vec<(u64, AccountId)> = events.iter()
+        .filter_map(|event| match event {
+            Event::NftReleased(event) => Some((event.auction_id, event.receiver)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(released, vec![(0, accounts.eve), (1, accounts.bob)]);
+}
+
+#[ink::test]
+fn nft_cancel_test() {
+    mocks::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let (collection, nft_storage) = (accounts.frank, accounts.charlie);
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, nft_storage);
+    mock_psp34::mint(collection, accounts.bob, Id::U8(1));
+    mock_psp34::approve(collection, accounts.bob, ink_env::account_id::<DefaultEnvironment>(), Some(Id::U8(1)));
+    assert_call_result_eq(create_nft_auction(&mut contract, collection, 1), Ok(0));
+
+    set_caller_and_update_balance(accounts.eve, Some(1000));
+    set_value_transferred(100);
+    assert_call_result_eq(contract.bid(0), Ok(3));
+
+    // owner gets the token back, the bidder gets the bid back
+    set_caller_and_update_balance(accounts.bob, None);
+    set_value_transferred(contract.get_finalize_fee_of(0).unwrap());
+    assert_call_result_eq(contract.cancel_auction(0), Ok(()));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(1)), Some(accounts.bob));
+    set_caller_and_update_balance(accounts.eve, None);
+    set_value_transferred(0);
+    assert_call_result_eq(contract.withdraw(), Ok(100));
+}
+
+#[ink::test]
+fn nft_storage_error_test() {
+    mocks::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let (collection, nft_storage) = (accounts.frank, accounts.charlie);
+    let mut contract = Alleblock::new(10, 500, 500, accounts.django, nft_storage);
+    mock_psp34::approve(collection, accounts.bob, ink_env::account_id::<DefaultEnvironment>(), None);
+    for token in 1..5 {
+        mock_psp34::mint(collection, accounts.bob, Id::U8(token));
+        assert_call_result_eq(create_nft_auction(&mut contract, collection, token), Ok(token as u64 - 1));
+    }
+    set_value_transferred(0);
+    ink_env::test::advance_block::<DefaultEnvironment>();
+
+    // nft storage which can't release the token fails the finish, so the auction can be finished later
+    // Note: on-chain the failed transaction reverts the finish, off-chain it's kept, so every case has its own auction
+    assert_eq!(mock_psp34::transfer(collection, nft_storage, accounts.django, Id::U8(1)), Ok(()));
+    assert_call_result_eq(contract.finish_auction(0), Err(Error::NftStorageTransferError(PSP34Error::NotApproved)));
+
+    // nft storage which can't be called or answers garbage fails the finish too
+    mocks::fail_next_storage_call(ink_env::Error::CalleeTrapped);
+    assert_call_result_eq(contract.finish_auction(1), Err(Error::NftStorageCallError(CallFailure::CalleeTrapped)));
+    mocks::fail_next_storage_call(ink_env::Error::NotCallable);
+    assert_call_result_eq(contract.finish_auction(2), Err(Error::NftStorageCallError(CallFailure::NotCallable)));
+    mocks::fail_next_storage_call(ink_env::Error::Decode(scale::Error::from("bad answer")));
+    assert_call_result_eq(contract.finish_auction(3), Err(Error::NftStorageDecodeError));
+}
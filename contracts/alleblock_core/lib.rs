@@ -0,0 +1,414 @@
+//! auction state machine and fee maths of the alleblock contract
+//! plain Rust without the ink! environment, the ink feature adds the traits needed to keep the types in contract storage
+#![cfg_attr(not(feature = "std"), no_std)]
+// functions end with an explicit return, same as in the contracts
+#![allow(clippy::needless_return)]
+
+#[cfg(feature = "ink")]
+use ink_storage::traits::{PackedLayout, SpreadLayout};
+
+/// miliseconds since the unix epoch, same as the contracts' block timestamp
+pub type Timestamp = u64;
+
+/// fee rates are given in basis points, 10_000 basis points is the whole amount
+pub const MAX_FEE_BPS: u32 = 10_000;
+
+/// duration bounds used until the contract owner sets them, auctions last from 1 milisecond to 365 days
+pub const DEFAULT_DURATION_BOUNDS: DurationBounds = DurationBounds { min: 1, max: 365 * 24 * 60 * 60 * 1000 };
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub enum AuctionState {
+    InProgress,
+    Finished,
+    Cancelled,
+    Commit,
+    Reveal,
+    ReserveNotMet
+}
+
+impl AuctionState {
+    /// whether the auction can still be finished or cancelled
+    pub fn is_open(&self) -> bool {
+        return matches!(self, AuctionState::InProgress | AuctionState::Commit | AuctionState::Reveal);
+    }
+}
+
+/// minimal raise over the highest bid of an english auction
+/// Absolute -- in the smallest chunk of currency
+/// BasisPoints -- in 1/10000 of the highest bid, rounded up
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub enum BidIncrement {
+    Absolute(u128),
+    BasisPoints(u32)
+}
+
+/// buy-it-now offer of an english auction
+/// anyone can end the auction right away by paying <price>
+/// offer is withdrawn once the highest bid passes <threshold> or reaches <price>
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub struct BuyNow {
+    pub price: u128,
+    pub threshold: u128,
+}
+
+/// price paid by the winner of a sealed-bid auction
+/// FirstPrice -- winner pays own bid
+/// SecondPrice -- winner pays the second highest revealed bid, but not less than the starting bid (Vickrey auction)
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub enum SealedPricing {
+    FirstPrice,
+    SecondPrice
+}
+
+/// what happens with deposits of sealed bids that were never revealed
+/// Refund -- deposit is given back to the bidder
+/// Forfeit -- deposit is given to the auction owner
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub enum UnrevealedDeposit {
+    Refund,
+    Forfeit
+}
+
+/// how the price of an auction is set
+/// English -- bidders outbid each other until the finish date
+/// Dutch -- price falls linearly from <start_price> at the creation date to <floor_price> at the finish date,
+///          the first buyer who pays the current price wins
+/// Sealed -- bidders commit hashes of their bids until <reveal_date>, then reveal them until the finish date,
+///           highest revealed bid wins and pays according to <pricing>
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub enum AuctionKind {
+    English,
+    Dutch { start_price: u128, floor_price: u128 },
+    Sealed { pricing: SealedPricing, reveal_date: Timestamp, unrevealed: UnrevealedDeposit }
+}
+
+/// anti-sniping settings of an auction
+/// every bid placed less than <window> miliseconds before the finish date
+/// pushes the finish date by <extension> miliseconds
+/// auction can't be extended by more than <max_total_extension> miliseconds in total
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub struct SoftClose {
+    pub window: u64,
+    pub extension: u64,
+    pub max_total_extension: u64,
+}
+
+/// shortest and longest allowed duration of new auctions, in miliseconds
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub struct DurationBounds {
+    pub min: u64,
+    pub max: u64,
+}
+
+/// fees of an auction, fixed when the auction is created
+/// settlement_fee_bps -- fraction of the final price that contract takes as fee, in basis points
+/// cancellation_fee_bps -- fraction of the highest bid that auction owner pays to cancel the auction, in basis points
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(PackedLayout, SpreadLayout, scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub struct FeeTerms {
+    pub settlement_fee_bps: u32,
+    pub cancellation_fee_bps: u32,
+}
+
+/// fees of settling or cancelling an auction right now, see FeeTerms::quote
+/// price -- price at which the auction would be settled
+/// seller_amount -- part of the price which the auction owner gets
+/// platform_fee -- part of the price which the contract owner gets
+/// cancellation_cost -- fee the auction owner has to pay to cancel the auction
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "ink", derive(scale::Encode, scale::Decode))]
+#[cfg_attr(all(feature = "ink", feature = "std"), derive(scale_info::TypeInfo))]
+pub struct FeeQuote {
+    pub price: u128,
+    pub seller_amount: u128,
+    pub platform_fee: u128,
+    pub cancellation_cost: u128,
+}
+
+/// reasons for which the auction rules reject an action, contracts turn them into their own errors
+/// TooLowBid -- carries the lowest amount which would be accepted
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CoreError {
+    TooLowBid(u128),
+    BuyNowUnavailable,
+    WrongAuctionKind,
+    InvalidDuration
+}
+
+/// fee of <fee_bps> basis points of the amount, rounded down
+pub fn fee_of(amount: u128, fee_bps: u32) -> u128 {
+    // split the amount, so the multiplication can't overflow
+    let fee_bps = fee_bps as u128;
+    return amount / 10_000 * fee_bps + amount % 10_000 * fee_bps / 10_000;
+}
+
+impl FeeTerms {
+    /// how the price is split between the auction owner and the contract owner, and the cost of cancelling at the highest bid
+    pub fn quote(&self, price: u128, highest_bid: u128) -> FeeQuote {
+        let platform_fee = fee_of(price, self.settlement_fee_bps);
+        return FeeQuote {
+            price,
+            seller_amount: price - platform_fee,
+            platform_fee,
+            cancellation_cost: fee_of(highest_bid, self.cancellation_fee_bps),
+        };
+    }
+}
+
+/// highest bid of an english auction
+/// bid -- current highest bid, 0 if nobody has bid yet
+/// max -- most the bidder agreed to pay, the money held for the bidder
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Lead<A> {
+    pub bidder: A,
+    pub bid: u128,
+    pub max: u128,
+}
+
+/// result of a bid on an english auction
+/// lead -- highest bid after the bid
+/// refund -- money given back, to the previous leader or to the bidder
+/// outbid -- whether the refunded account lost the lead, or was only raising own bid
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BidOutcome<A> {
+    pub lead: Lead<A>,
+    pub refund: Option<(A, u128)>,
+    pub outbid: bool,
+}
+
+/// amount raised by the minimal increment of an english auction
+pub fn raise(min_increment: &Option<BidIncrement>, amount: u128) -> u128 {
+    let increment = match min_increment {
+        Some(BidIncrement::Absolute(increment)) => *increment,
+        Some(BidIncrement::BasisPoints(basis_points)) => {
+            let scaled = amount.saturating_mul(*basis_points as u128);
+            scaled / 10_000 + if !scaled.is_multiple_of(10_000) { 1 } else { 0 }
+        },
+        None => 1
+    };
+
+    return amount.saturating_add(core::cmp::max(increment, 1));
+}
+
+/// lowest amount which can be bid on an english auction
+/// the starting bid if nobody has bid yet, otherwise the highest bid raised by the minimal increment
+/// Note: bid is at least 1, as highest bid 0 means nobody has bid
+pub fn min_next_bid(starting_bid: u128, highest_bid: u128, min_increment: &Option<BidIncrement>) -> u128 {
+    if highest_bid == 0 {
+        return core::cmp::max(starting_bid, 1);
+    }
+    return core::cmp::max(starting_bid, raise(min_increment, highest_bid));
+}
+
+/// bid <amount> on an english auction, as a maximal bid if <proxy> is set
+/// proxy bids are raised automatically, just enough to keep the lead, until they reach their maximum
+pub fn place_bid<A: Copy + PartialEq>(
+    lead: Lead<A>,
+    starting_bid: u128,
+    min_increment: &Option<BidIncrement>,
+    bidder: A,
+    amount: u128,
+    proxy: bool
+) -> Result<BidOutcome<A>, CoreError> {
+    let min_next_bid = min_next_bid(starting_bid, lead.bid, min_increment);
+    if amount < min_next_bid {
+        return Err(CoreError::TooLowBid(min_next_bid));
+    }
+
+    if lead.bid > 0 && bidder == lead.bidder {
+        // the highest bidder replaces own bid, previous money is given back
+        let bid = if proxy { lead.bid } else { amount };
+        return Ok(BidOutcome { lead: Lead { bidder, bid, max: amount }, refund: Some((bidder, lead.max)), outbid: false });
+    }
+
+    if lead.bid > 0 && amount <= lead.max {
+        // proxy bid of the highest bidder outbids the new bid, which is given back
+        let bid = core::cmp::min(lead.max, raise(min_increment, amount));
+        return Ok(BidOutcome { lead: Lead { bid, ..lead }, refund: Some((bidder, amount)), outbid: true });
+    }
+
+    // new bid outbids the highest bidder, who gets the money back
+    let refund = if lead.bid > 0 { Some((lead.bidder, lead.max)) } else { None };
+    let outbidding_bid = if lead.bid > 0 { raise(min_increment, lead.max) } else { starting_bid };
+    let bid = if proxy { core::cmp::min(amount, core::cmp::max(min_next_bid, outbidding_bid)) } else { amount };
+    return Ok(BidOutcome { lead: Lead { bidder, bid, max: amount }, refund, outbid: refund.is_some() });
+}
+
+/// finish date of an auction created at <creation_date>
+/// duration has to be within the bounds, also when extended by soft close as much as possible
+pub fn finish_date(creation_date: Timestamp, duration: u64, bounds: &DurationBounds, soft_close: &Option<SoftClose>) -> Result<Timestamp, CoreError> {
+    let max_total_extension = soft_close.as_ref().map_or(0, |soft_close| soft_close.max_total_extension);
+    let longest_duration = duration.checked_add(max_total_extension).ok_or(CoreError::InvalidDuration)?;
+    if duration < bounds.min || longest_duration > bounds.max {
+        return Err(CoreError::InvalidDuration);
+    }
+
+    let finish_date = creation_date.checked_add(duration).ok_or(CoreError::InvalidDuration)?;
+    if soft_close.as_ref().is_some_and(|soft_close| finish_date.checked_add(soft_close.max_total_extension).is_none()) {
+        return Err(CoreError::InvalidDuration);
+    }
+
+    return Ok(finish_date);
+}
+
+/// finish date and total extension after a bid at <timestamp>
+/// the auction is extended if the bid came in the soft close window
+pub fn extend(soft_close: &SoftClose, finish_date: Timestamp, extended_by: u64, timestamp: Timestamp) -> Result<(Timestamp, u64), CoreError> {
+    if timestamp.saturating_add(soft_close.window) <= finish_date {
+        return Ok((finish_date, extended_by));
+    }
+
+    let extension = core::cmp::min(soft_close.extension, soft_close.max_total_extension - extended_by);
+    let finish_date = finish_date.checked_add(extension).ok_or(CoreError::InvalidDuration)?;
+    return Ok((finish_date, extended_by + extension));
+}
+
+/// move the deadlines of an open auction by a pause of <pause_length> miliseconds which started at <pause_start>
+/// deadlines which were over before the pause started don't move
+/// price curve of a dutch auction starts at its creation date, which moves too, so the price continues from where it was paused
+/// deadlines can't overflow, at worst the auction ends at the end of time
+pub fn postpone(creation_date: &mut Timestamp, finish_date: &mut Timestamp, kind: &mut AuctionKind, pause_start: Timestamp, pause_length: u64) {
+    if pause_start > *finish_date {
+        return;
+    }
+
+    *finish_date = finish_date.saturating_add(pause_length);
+    if let AuctionKind::Dutch { .. } = kind {
+        *creation_date = creation_date.saturating_add(pause_length);
+    }
+    if let AuctionKind::Sealed { reveal_date, .. } = kind {
+        if pause_start <= *reveal_date {
+            *reveal_date = reveal_date.saturating_add(pause_length);
+        }
+    }
+}
+
+/// whether the deadline is over at <timestamp>, deadline is frozen if the current pause started before it
+pub fn deadline_passed(deadline: Timestamp, timestamp: Timestamp, paused_since: Option<Timestamp>) -> bool {
+    if paused_since.is_some_and(|paused_since| paused_since <= deadline) {
+        return false;
+    }
+    return timestamp > deadline;
+}
+
+/// price of a dutch auction at given time
+pub fn dutch_price(start_price: u128, floor_price: u128, creation_date: Timestamp, finish_date: Timestamp, timestamp: Timestamp) -> u128 {
+    if timestamp >= finish_date {
+        return floor_price;
+    }
+
+    // (start_price - floor_price) * elapsed / duration, split so it can't overflow
+    let elapsed = timestamp.saturating_sub(creation_date) as u128;
+    let duration = (finish_date - creation_date) as u128;
+    let price_range = start_price - floor_price;
+    let price_drop = price_range / duration * elapsed + price_range % duration * elapsed / duration;
+
+    return start_price - price_drop;
+}
+
+/// price for which the item can be bought right away at given time
+/// dutch auction is bought for its current price, english auction for its buy-now price
+pub fn buy_now_price(
+    kind: &AuctionKind,
+    buy_now: &Option<BuyNow>,
+    highest_bid: u128,
+    creation_date: Timestamp,
+    finish_date: Timestamp,
+    timestamp: Timestamp
+) -> Result<u128, CoreError> {
+    return match (kind, buy_now) {
+        (AuctionKind::Dutch { start_price, floor_price }, _) => {
+            Ok(dutch_price(*start_price, *floor_price, creation_date, finish_date, timestamp))
+        },
+        (AuctionKind::English, Some(offer)) => {
+            if highest_bid > offer.threshold || highest_bid >= offer.price {
+                return Err(CoreError::BuyNowUnavailable);
+            }
+            Ok(offer.price)
+        },
+        (AuctionKind::English, None) => Err(CoreError::BuyNowUnavailable),
+        _ => Err(CoreError::WrongAuctionKind)
+    };
+}
+
+/// price the winner pays when the auction is finished
+/// winner of a second-price sealed-bid auction pays the second highest bid, but not less than the starting bid
+pub fn settlement_price(kind: &AuctionKind, starting_bid: u128, highest_bid: u128, second_highest_bid: u128) -> u128 {
+    return match kind {
+        AuctionKind::Sealed { pricing: SealedPricing::SecondPrice, .. } if highest_bid > 0 => {
+            core::cmp::max(second_highest_bid, starting_bid)
+        },
+        _ => highest_bid
+    };
+}
+
+/// how the money of a finished auction is split
+/// reserve_met -- false if the reserve price wasn't reached or revealed, then the winner gets back <winner_refund> and nothing else is paid
+/// price -- price the winner pays
+/// winner_refund -- money held for the winner which isn't needed to pay the price
+/// seller_amount -- part of the price which the auction owner gets
+/// platform_fee -- part of the price which the contract owner gets
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Settlement {
+    pub reserve_met: bool,
+    pub price: u128,
+    pub winner_refund: u128,
+    pub seller_amount: u128,
+    pub platform_fee: u128,
+}
+
+/// bids and reserve of an auction which is settled, see settle
+/// reserve_committed -- whether the auction has a hidden reserve, which isn't met until <reserve> is revealed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FinishedAuction<'a> {
+    pub kind: &'a AuctionKind,
+    pub starting_bid: u128,
+    pub highest_bid: u128,
+    pub highest_bidder_max: u128,
+    pub second_highest_bid: u128,
+    pub reserve: Option<u128>,
+    pub reserve_committed: bool,
+}
+
+/// settle an auction after its finish date
+pub fn settle(auction: &FinishedAuction, fee_terms: &FeeTerms) -> Settlement {
+    // check if the reserve price was revealed and met
+    let reserve_met = match auction.reserve {
+        Some(reserve) => auction.highest_bid >= reserve,
+        None => !auction.reserve_committed
+    };
+    if !reserve_met {
+        let winner_refund = if auction.highest_bid > 0 { auction.highest_bidder_max } else { 0 };
+        return Settlement { reserve_met: false, price: 0, winner_refund, seller_amount: 0, platform_fee: 0 };
+    }
+
+    let price = settlement_price(auction.kind, auction.starting_bid, auction.highest_bid, auction.second_highest_bid);
+    let quote = fee_terms.quote(price, auction.highest_bid);
+    return Settlement {
+        reserve_met: true,
+        price,
+        winner_refund: auction.highest_bidder_max.saturating_sub(price),
+        seller_amount: quote.seller_amount,
+        platform_fee: quote.platform_fee,
+    };
+}
+
+#[cfg(test)]
+mod tests;
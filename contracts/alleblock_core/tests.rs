@@ -0,0 +1,260 @@
+use crate::*;
+
+// helper functions
+
+fn english_lead(bidder: u8, bid: u128, max: u128) -> Lead<u8> {
+    return Lead { bidder, bid, max };
+}
+
+fn sealed(pricing: SealedPricing) -> AuctionKind {
+    return AuctionKind::Sealed { pricing, reveal_date: 50, unrevealed: UnrevealedDeposit::Refund };
+}
+
+// tests
+
+#[test]
+fn fee_of_test() {
+    assert_eq!(fee_of(1000, 250), 25);
+    assert_eq!(fee_of(99, 100), 0);
+    assert_eq!(fee_of(u128::MAX, MAX_FEE_BPS), u128::MAX);
+    assert_eq!(fee_of(u128::MAX, 0), 0);
+}
+
+#[test]
+fn raise_test() {
+    assert_eq!(raise(&None, 10), 11);
+    assert_eq!(raise(&Some(BidIncrement::Absolute(5)), 10), 15);
+    assert_eq!(raise(&Some(BidIncrement::Absolute(0)), 10), 11);
+    // basis points are rounded up
+    assert_eq!(raise(&Some(BidIncrement::BasisPoints(500)), 100), 105);
+    assert_eq!(raise(&Some(BidIncrement::BasisPoints(500)), 101), 107);
+    assert_eq!(raise(&Some(BidIncrement::Absolute(5)), u128::MAX), u128::MAX);
+}
+
+#[test]
+fn min_next_bid_test() {
+    assert_eq!(min_next_bid(10, 0, &None), 10);
+    assert_eq!(min_next_bid(0, 0, &None), 1);
+    assert_eq!(min_next_bid(10, 20, &None), 21);
+    assert_eq!(min_next_bid(100, 20, &None), 100);
+    assert_eq!(min_next_bid(10, 20, &Some(BidIncrement::Absolute(5))), 25);
+}
+
+#[test]
+fn place_bid_test() {
+    // first bid has to reach the starting bid
+    let nobody = english_lead(0, 0, 0);
+    assert_eq!(place_bid(nobody, 10, &None, 1, 9, false), Err(CoreError::TooLowBid(10)));
+    // bid of 0 doesn't count, even if the auction starts at 0
+    assert_eq!(place_bid(nobody, 0, &None, 1, 0, false), Err(CoreError::TooLowBid(1)));
+    assert_eq!(place_bid(nobody, 0, &None, 1, 0, true), Err(CoreError::TooLowBid(1)));
+    assert_eq!(
+        place_bid(nobody, 10, &None, 1, 15, false),
+        Ok(BidOutcome { lead: english_lead(1, 15, 15), refund: None, outbid: false })
+    );
+
+    // proxy bid starts at the starting bid
+    assert_eq!(
+        place_bid(nobody, 10, &None, 1, 15, true),
+        Ok(BidOutcome { lead: english_lead(1, 10, 15), refund: None, outbid: false })
+    );
+
+    // new bid outbids the highest bidder
+    let lead = english_lead(1, 15, 15);
+    assert_eq!(place_bid(lead, 10, &None, 2, 15, false), Err(CoreError::TooLowBid(16)));
+    assert_eq!(
+        place_bid(lead, 10, &None, 2, 20, false),
+        Ok(BidOutcome { lead: english_lead(2, 20, 20), refund: Some((1, 15)), outbid: true })
+    );
+}
+
+#[test]
+fn place_proxy_bid_test() {
+    // proxy bid of the highest bidder outbids the new bid
+    let lead = english_lead(1, 10, 50);
+    assert_eq!(
+        place_bid(lead, 10, &None, 2, 20, false),
+        Ok(BidOutcome { lead: english_lead(1, 21, 50), refund: Some((2, 20)), outbid: true })
+    );
+
+    // proxy bid never goes above its maximum
+    assert_eq!(
+        place_bid(lead, 10, &None, 2, 50, false),
+        Ok(BidOutcome { lead: english_lead(1, 50, 50), refund: Some((2, 50)), outbid: true })
+    );
+
+    // new proxy bid outbids the maximum just enough
+    assert_eq!(
+        place_bid(lead, 10, &Some(BidIncrement::Absolute(5)), 2, 100, true),
+        Ok(BidOutcome { lead: english_lead(2, 55, 100), refund: Some((1, 50)), outbid: true })
+    );
+
+    // the highest bidder raises own maximum, the bid stays
+    assert_eq!(
+        place_bid(lead, 10, &None, 1, 80, true),
+        Ok(BidOutcome { lead: english_lead(1, 10, 80), refund: Some((1, 50)), outbid: false })
+    );
+}
+
+#[test]
+fn finish_date_test() {
+    let bounds = DurationBounds { min: 10, max: 100 };
+    assert_eq!(finish_date(1000, 9, &bounds, &None), Err(CoreError::InvalidDuration));
+    assert_eq!(finish_date(1000, 101, &bounds, &None), Err(CoreError::InvalidDuration));
+    assert_eq!(finish_date(1000, 10, &bounds, &None), Ok(1010));
+    assert_eq!(finish_date(1000, 100, &bounds, &None), Ok(1100));
+
+    // soft close can't extend the auction past the longest duration
+    let soft_close = Some(SoftClose { window: 10, extension: 10, max_total_extension: 11 });
+    assert_eq!(finish_date(1000, 90, &bounds, &soft_close), Err(CoreError::InvalidDuration));
+    assert_eq!(finish_date(1000, 89, &bounds, &soft_close), Ok(1089));
+
+    // finish date can't overflow, even when extended
+    let bounds = DurationBounds { min: 1, max: u64::MAX };
+    assert_eq!(finish_date(u64::MAX - 5, 6, &bounds, &None), Err(CoreError::InvalidDuration));
+    let soft_close = Some(SoftClose { window: 10, extension: 10, max_total_extension: 5 });
+    assert_eq!(finish_date(u64::MAX - 10, 6, &bounds, &soft_close), Err(CoreError::InvalidDuration));
+    assert_eq!(finish_date(u64::MAX - 11, 6, &bounds, &soft_close), Ok(u64::MAX - 5));
+}
+
+#[test]
+fn extend_test() {
+    let soft_close = SoftClose { window: 10, extension: 10, max_total_extension: 15 };
+
+    // bid before the window doesn't extend the auction
+    assert_eq!(extend(&soft_close, 100, 0, 90), Ok((100, 0)));
+
+    // bid in the window does, up to the maximal extension
+    assert_eq!(extend(&soft_close, 100, 0, 91), Ok((110, 10)));
+    assert_eq!(extend(&soft_close, 110, 10, 105), Ok((115, 15)));
+    assert_eq!(extend(&soft_close, 115, 15, 110), Ok((115, 15)));
+}
+
+#[test]
+fn postpone_test() {
+    // deadlines over before the pause don't move
+    let (mut finish_date, mut kind) = (100, sealed(SealedPricing::FirstPrice));
+    postpone(&mut 0, &mut finish_date, &mut kind, 101, 20);
+    assert_eq!((finish_date, kind), (100, sealed(SealedPricing::FirstPrice)));
+
+    // deadlines after the pause start move by its length
+    let (mut finish_date, mut kind) = (100, sealed(SealedPricing::FirstPrice));
+    postpone(&mut 0, &mut finish_date, &mut kind, 40, 20);
+    assert_eq!(finish_date, 120);
+    assert_eq!(kind, AuctionKind::Sealed { pricing: SealedPricing::FirstPrice, reveal_date: 70, unrevealed: UnrevealedDeposit::Refund });
+
+    // reveal date over before the pause stays
+    let (mut finish_date, mut kind) = (100, sealed(SealedPricing::FirstPrice));
+    postpone(&mut 0, &mut finish_date, &mut kind, 60, 20);
+    assert_eq!((finish_date, kind), (120, sealed(SealedPricing::FirstPrice)));
+
+    // dutch price curve starts later, so the price at the end of the pause is the one from its start
+    let (mut creation_date, mut finish_date, mut kind) = (0, 100, AuctionKind::Dutch { start_price: 100, floor_price: 0 });
+    assert_eq!(dutch_price(100, 0, creation_date, finish_date, 50), 50);
+    postpone(&mut creation_date, &mut finish_date, &mut kind, 50, 100);
+    assert_eq!((creation_date, finish_date), (100, 200));
+    assert_eq!(dutch_price(100, 0, creation_date, finish_date, 150), 50);
+
+    // deadlines can't overflow
+    let (mut finish_date, mut kind) = (u64::MAX - 1, AuctionKind::English);
+    postpone(&mut 0, &mut finish_date, &mut kind, 0, 20);
+    assert_eq!(finish_date, u64::MAX);
+}
+
+#[test]
+fn deadline_passed_test() {
+    assert!(!deadline_passed(100, 100, None));
+    assert!(deadline_passed(100, 101, None));
+
+    // deadline is frozen by a pause which started before it
+    assert!(!deadline_passed(100, 200, Some(100)));
+    assert!(deadline_passed(100, 200, Some(101)));
+}
+
+#[test]
+fn dutch_price_test() {
+    assert_eq!(dutch_price(1000, 100, 0, 100, 0), 1000);
+    assert_eq!(dutch_price(1000, 100, 0, 100, 50), 550);
+    assert_eq!(dutch_price(1000, 100, 0, 100, 100), 100);
+    assert_eq!(dutch_price(1000, 100, 0, 100, 1000), 100);
+    assert_eq!(dutch_price(u128::MAX, 0, 0, u64::MAX, u64::MAX - 1), u128::MAX / u64::MAX as u128);
+}
+
+#[test]
+fn buy_now_price_test() {
+    let offer = Some(BuyNow { price: 100, threshold: 50 });
+    assert_eq!(buy_now_price(&AuctionKind::English, &offer, 0, 0, 100, 10), Ok(100));
+    assert_eq!(buy_now_price(&AuctionKind::English, &offer, 50, 0, 100, 10), Ok(100));
+
+    // offer is withdrawn once the highest bid passes the threshold
+    assert_eq!(buy_now_price(&AuctionKind::English, &offer, 51, 0, 100, 10), Err(CoreError::BuyNowUnavailable));
+    assert_eq!(buy_now_price(&AuctionKind::English, &None, 0, 0, 100, 10), Err(CoreError::BuyNowUnavailable));
+
+    let dutch = AuctionKind::Dutch { start_price: 200, floor_price: 100 };
+    assert_eq!(buy_now_price(&dutch, &None, 0, 0, 100, 10), Ok(190));
+    assert_eq!(buy_now_price(&sealed(SealedPricing::FirstPrice), &offer, 0, 0, 100, 10), Err(CoreError::WrongAuctionKind));
+}
+
+#[test]
+fn settle_test() {
+    let fee_terms = FeeTerms { settlement_fee_bps: 1000, cancellation_fee_bps: 500 };
+    let english = FinishedAuction {
+        kind: &AuctionKind::English,
+        starting_bid: 10,
+        highest_bid: 100,
+        highest_bidder_max: 150,
+        second_highest_bid: 0,
+        reserve: None,
+        reserve_committed: false,
+    };
+
+    // english auction is settled at the highest bid, the rest of the proxy maximum is given back
+    assert_eq!(
+        settle(&english, &fee_terms),
+        Settlement { reserve_met: true, price: 100, winner_refund: 50, seller_amount: 90, platform_fee: 10 }
+    );
+
+    // second-price auction is settled at the second bid, but not less than the starting bid
+    let second_price = sealed(SealedPricing::SecondPrice);
+    assert_eq!(
+        settle(&FinishedAuction { kind: &second_price, highest_bidder_max: 100, second_highest_bid: 60, ..english.clone() }, &fee_terms),
+        Settlement { reserve_met: true, price: 60, winner_refund: 40, seller_amount: 54, platform_fee: 6 }
+    );
+    assert_eq!(settlement_price(&second_price, 80, 100, 60), 80);
+    assert_eq!(settlement_price(&second_price, 80, 0, 0), 0);
+
+    // reserve which isn't met gives the highest bid back
+    assert_eq!(
+        settle(&FinishedAuction { reserve: Some(101), reserve_committed: true, ..english.clone() }, &fee_terms),
+        Settlement { reserve_met: false, price: 0, winner_refund: 150, seller_amount: 0, platform_fee: 0 }
+    );
+    assert_eq!(
+        settle(&FinishedAuction { highest_bid: 0, highest_bidder_max: 0, reserve: Some(101), reserve_committed: true, ..english.clone() }, &fee_terms),
+        Settlement { reserve_met: false, price: 0, winner_refund: 0, seller_amount: 0, platform_fee: 0 }
+    );
+
+    // hidden reserve which was never revealed isn't met either
+    assert_eq!(
+        settle(&FinishedAuction { reserve_committed: true, ..english }, &fee_terms),
+        Settlement { reserve_met: false, price: 0, winner_refund: 150, seller_amount: 0, platform_fee: 0 }
+    );
+}
+
+#[test]
+fn quote_test() {
+    let fee_terms = FeeTerms { settlement_fee_bps: 1000, cancellation_fee_bps: 500 };
+    assert_eq!(
+        fee_terms.quote(80, 100),
+        FeeQuote { price: 80, seller_amount: 72, platform_fee: 8, cancellation_cost: 5 }
+    );
+}
+
+#[test]
+fn auction_state_test() {
+    assert!(AuctionState::InProgress.is_open());
+    assert!(AuctionState::Commit.is_open());
+    assert!(AuctionState::Reveal.is_open());
+    assert!(!AuctionState::Finished.is_open());
+    assert!(!AuctionState::Cancelled.is_open());
+    assert!(!AuctionState::ReserveNotMet.is_open());
+}
@@ -2,19 +2,58 @@
 
 use ink_lang as ink;
 
+pub use self::nft_storage::{NftStorage, NftStorageRef};
+
+// tokens of off-chain tests are kept in mock collections, also by tests of contracts calling nft storage
+#[cfg(any(test, feature = "mock-psp34"))]
+pub mod mock_psp34;
+#[cfg(test)]
+mod tests;
+
 #[ink::contract]
 mod nft_storage {
     use openbrush::contracts::traits::psp34::Id;
     use openbrush::contracts::psp34::PSP34Error;
     use openbrush::contracts::traits::psp34::PSP34Ref;
     use ink_prelude::string::{ToString};
+    use ink_primitives::Key;
 
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
-        NotAnOwnerError
+        NotAnOwnerError,
+        NotAnAdminError,
+        UpgradeError
+    }
+
+    /// stored token was transferred to the receiver
+    #[ink(event)]
+    pub struct NftTransferred {
+        #[ink(topic)]
+        pub to: AccountId,
+        #[ink(topic)]
+        pub nft_account: AccountId,
+        pub nft_token: Id,
+    }
+
+    /// contract code was replaced, storage stays the same
+    #[ink(event)]
+    pub struct CodeUpgraded {
+        pub code_hash: [u8; 32],
     }
 
+    /// storage cell keeping the version of the storage layout, written by the constructor
+    const STORAGE_VERSION_KEY: [u8; 32] = [0xff; 32];
+
+    /// version of the storage layout used by this code, see migrate_storage
+    /// 1 -- owner only, first layout which can be upgraded with set_code
+    const STORAGE_VERSION: u32 = 1;
+
+    /// storage cell keeping the admin, who replaces the code and migrates the storage
+    /// the owner is alleblock, which can't call set_code, so the admin is kept apart from it
+    /// kept outside of the contract struct, because reading a missing struct field fails before any message runs
+    const ADMIN_KEY: [u8; 32] = [0xfe; 32];
+
     #[ink(storage)]
     pub struct NftStorage {
         owner: AccountId,
@@ -22,10 +61,34 @@ mod nft_storage {
 
     pub type Result<T> = core::result::Result<T, Error>;
 
+    /// PSP34 collections of the stored tokens
+    /// Note: off-chain tests can't call other contracts, so test builds move tokens in the mock collections of mock_psp34.rs
+    pub trait Collections {
+        /// same as PSP34::transfer called by this contract
+        fn transfer(collection: AccountId, to: AccountId, id: Id) -> core::result::Result<(), PSP34Error>;
+    }
+
+    /// collections called on-chain, through PSP34Ref
+    #[cfg_attr(test, allow(dead_code))]
+    pub struct OnChain;
+
+    impl Collections for OnChain {
+        fn transfer(collection: AccountId, to: AccountId, id: Id) -> core::result::Result<(), PSP34Error> {
+            return PSP34Ref::transfer(&collection, to, id, [0x0].to_vec());
+        }
+    }
+
+    #[cfg(not(test))]
+    type Collection = OnChain;
+    #[cfg(test)]
+    type Collection = crate::mock_psp34::MockCollections;
+
     impl NftStorage {
-        /// Constructor that remembers creator of this contract
+        /// Constructor that remembers creator of this contract, as the owner and the admin
         #[ink(constructor)]
         pub fn new() -> Self {
+            ink_env::set_contract_storage_return_size(&Key::from(STORAGE_VERSION_KEY), &STORAGE_VERSION);
+            ink_env::set_contract_storage_return_size(&Key::from(ADMIN_KEY), &Self::env().caller());
             Self { 
                 owner: Self::env().caller(),
             }
@@ -47,13 +110,74 @@ mod nft_storage {
             return self.owner.clone();
         }
 
+        /// Set admin of this contract, only admin can call this message
+        #[ink(message)]
+        pub fn set_admin(&mut self, admin: AccountId) -> Result<()> {
+            if self.env().caller() != self.admin() {
+                return Err(Error::NotAnAdminError)
+            }
+            ink_env::set_contract_storage_return_size(&Key::from(ADMIN_KEY), &admin);
+            return Ok(());
+        }
+
+        /// Get contracts admin
+        #[ink(message)]
+        pub fn get_admin(&self) -> AccountId {
+            return self.admin();
+        }
+
+        /// Replace the code of this contract, keeping stored tokens
+        /// only admin can call this message
+        /// code_hash -- hash of the new code, which has to be uploaded to the chain already
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            if self.env().caller() != self.admin() {
+                return Err(Error::NotAnAdminError)
+            }
+            if ink_env::set_code_hash(&code_hash).is_err() {
+                return Err(Error::UpgradeError)
+            }
+            self.env().emit_event(CodeUpgraded { code_hash });
+            return Ok(());
+        }
+
+        /// Bring the storage written by older code to the layout used by this code
+        /// version 1 is the first layout, steps from older versions are added here when the layout changes
+        /// only admin can call this message
+        /// returns the storage version after the migration
+        #[ink(message)]
+        pub fn migrate_storage(&mut self) -> Result<u32> {
+            if self.env().caller() != self.admin() {
+                return Err(Error::NotAnAdminError)
+            }
+            // storage of this version needs nothing, there is no step for other versions
+            let version = self.get_storage_version();
+            if version != STORAGE_VERSION {
+                return Err(Error::UpgradeError)
+            }
+            return Ok(version);
+        }
+
+        /// Get version of the storage layout, 0 if it was never written
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u32 {
+            return ink_env::get_contract_storage(&Key::from(STORAGE_VERSION_KEY)).ok().flatten().unwrap_or(0);
+        }
+
         /// transfer given token to given address
         #[ink(message)]
         pub fn transfer(&mut self, to: AccountId, nft_account: AccountId, nft_token: Id) ->core::result::Result<(), PSP34Error> { 
             if self.env().caller() != self.owner {
                 return Err(PSP34Error::Custom("NotAnOwnerError".to_string()))
             } 
-            return PSP34Ref::transfer(&nft_account, to, nft_token, [0x0].to_vec());
+            Collection::transfer(nft_account, to, nft_token.clone())?;
+            self.env().emit_event(NftTransferred { to, nft_account, nft_token });
+            return Ok(());
+        }
+
+        /// admin set in the storage cell, the owner for contracts deployed before the admin was kept
+        fn admin(&self) -> AccountId {
+            return ink_env::get_contract_storage(&Key::from(ADMIN_KEY)).ok().flatten().unwrap_or(self.owner);
         }
     }
 }
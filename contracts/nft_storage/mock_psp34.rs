@@ -0,0 +1,87 @@
+//! mock PSP34 collections for off-chain tests, which can't call other contracts
+//! test builds of nft storage move tokens in them, see Collections
+//! tests of contracts calling nft storage use them too, through the mock-psp34 feature
+use core::cell::RefCell;
+
+use crate::nft_storage::Collections;
+use ink_env::{AccountId, DefaultEnvironment};
+use openbrush::contracts::psp34::PSP34Error;
+use openbrush::contracts::traits::psp34::Id;
+
+/// tokens and approvals of every mock collection
+#[derive(Default)]
+struct Tokens {
+    /// (collection, token, owner)
+    owners: Vec<(AccountId, Id, AccountId)>,
+    /// (collection, owner, operator, token), no token means all tokens of the owner
+    approvals: Vec<(AccountId, AccountId, AccountId, Option<Id>)>,
+}
+
+thread_local! {
+    static TOKENS: RefCell<Tokens> = RefCell::new(Tokens::default());
+}
+
+/// forget all tokens and approvals, tests using the mock call it first
+pub fn reset() {
+    TOKENS.with(|tokens| *tokens.borrow_mut() = Tokens::default());
+}
+
+/// create a token of the collection owned by the owner
+pub fn mint(collection: AccountId, owner: AccountId, id: Id) {
+    TOKENS.with(|tokens| tokens.borrow_mut().owners.push((collection, id, owner)));
+}
+
+/// allow the operator to take the token of the owner, or all their tokens if no token is given
+pub fn approve(collection: AccountId, owner: AccountId, operator: AccountId, id: Option<Id>) {
+    TOKENS.with(|tokens| tokens.borrow_mut().approvals.push((collection, owner, operator, id)));
+}
+
+/// owner of the token, None if it was never minted
+pub fn owner_of(collection: AccountId, id: Id) -> Option<AccountId> {
+    return TOKENS.with(|tokens| {
+        tokens.borrow().owners.iter()
+            .find(|(c, i, _)| *c == collection && *i == id)
+            .map(|(_, _, owner)| *owner)
+    });
+}
+
+/// same as PSP34::allowance, approval of all tokens counts for every token
+pub fn allowance(collection: AccountId, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool {
+    return TOKENS.with(|tokens| {
+        tokens.borrow().approvals.iter().any(|(c, o, op, i)| {
+            *c == collection && *o == owner && *op == operator && (i.is_none() || *i == id)
+        })
+    });
+}
+
+/// same as PSP34::transfer called by the caller, who has to own the token or be allowed to take it
+/// approvals of the single token are dropped, as the token has a new owner
+pub fn transfer(collection: AccountId, caller: AccountId, to: AccountId, id: Id) -> Result<(), PSP34Error> {
+    let owner = match owner_of(collection, id.clone()) {
+        Some(x) => x,
+        None => return Err(PSP34Error::TokenNotExists)
+    };
+    if caller != owner && !allowance(collection, owner, caller, Some(id.clone())) {
+        return Err(PSP34Error::NotApproved);
+    }
+
+    TOKENS.with(|tokens| {
+        let mut tokens = tokens.borrow_mut();
+        tokens.approvals.retain(|(c, _, _, i)| !(*c == collection && *i == Some(id.clone())));
+        for (c, i, token_owner) in tokens.owners.iter_mut() {
+            if *c == collection && *i == id {
+                *token_owner = to;
+            }
+        }
+    });
+    return Ok(());
+}
+
+/// collections called by the contract under test, which is the account of the test environment
+pub struct MockCollections;
+
+impl Collections for MockCollections {
+    fn transfer(collection: AccountId, to: AccountId, id: Id) -> Result<(), PSP34Error> {
+        return transfer(collection, ink_env::account_id::<DefaultEnvironment>(), to, id);
+    }
+}
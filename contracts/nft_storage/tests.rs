@@ -0,0 +1,102 @@
+use crate::mock_psp34;
+use crate::nft_storage::*;
+use ink_env::{AccountId, DefaultEnvironment};
+use ink_lang as ink;
+use ink_primitives::Key;
+use openbrush::contracts::psp34::PSP34Error;
+use openbrush::contracts::traits::psp34::Id;
+
+// helper functions
+
+fn set_caller(account: AccountId) {
+    ink_env::test::set_caller::<DefaultEnvironment>(account);
+}
+
+type Event = <NftStorage as ink::reflect::ContractEventBase>::Type;
+
+fn recorded_events() -> Vec<Event> {
+    return ink_env::test::recorded_events()
+        .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+        .collect();
+}
+
+// tests
+
+#[ink::test]
+fn owner_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller(accounts.django);
+    let mut storage = NftStorage::new();
+    assert_eq!(storage.get_owner(), accounts.django);
+
+    // only owner hands the storage over
+    set_caller(accounts.bob);
+    assert_eq!(storage.set_owner(accounts.bob), Err(Error::NotAnOwnerError));
+    set_caller(accounts.django);
+    assert_eq!(storage.set_owner(accounts.frank), Ok(()));
+    assert_eq!(storage.get_owner(), accounts.frank);
+    assert_eq!(storage.set_owner(accounts.django), Err(Error::NotAnOwnerError));
+}
+
+#[ink::test]
+fn admin_test() {
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    set_caller(accounts.django);
+    let mut storage = NftStorage::new();
+    assert_eq!(storage.set_owner(accounts.frank), Ok(()));
+    assert_eq!(storage.get_admin(), accounts.django);
+
+    // owner doesn't upgrade the storage, the admin does
+    set_caller(accounts.frank);
+    assert_eq!(storage.set_code([0x01; 32]), Err(Error::NotAnAdminError));
+    assert_eq!(storage.migrate_storage(), Err(Error::NotAnAdminError));
+    assert_eq!(storage.set_admin(accounts.frank), Err(Error::NotAnAdminError));
+
+    // storage of the current version needs nothing, storage written by newer code isn't migrated
+    set_caller(accounts.django);
+    assert_eq!(storage.migrate_storage(), Ok(1));
+    ink_env::set_contract_storage(&Key::from([0xff; 32]), &2u32);
+    assert_eq!(storage.migrate_storage(), Err(Error::UpgradeError));
+    assert_eq!(storage.get_storage_version(), 2);
+    ink_env::set_contract_storage(&Key::from([0xff; 32]), &1u32);
+
+    // admin hands the upgrades over
+    assert_eq!(storage.set_admin(accounts.eve), Ok(()));
+    assert_eq!(storage.get_admin(), accounts.eve);
+    assert_eq!(storage.migrate_storage(), Err(Error::NotAnAdminError));
+
+    // storage deployed before the admin was kept is upgraded by its owner
+    ink_env::clear_contract_storage(&Key::from([0xfe; 32]));
+    assert_eq!(storage.get_admin(), accounts.frank);
+}
+
+#[ink::test]
+fn transfer_test() {
+    mock_psp34::reset();
+    let accounts = ink_env::test::default_accounts::<DefaultEnvironment>();
+    let collection = accounts.charlie;
+    let storage_account = ink_env::account_id::<DefaultEnvironment>();
+    set_caller(accounts.django);
+    let mut storage = NftStorage::new();
+    mock_psp34::mint(collection, storage_account, Id::U8(1));
+
+    // only owner takes tokens out of the storage
+    set_caller(accounts.bob);
+    assert_eq!(storage.transfer(accounts.bob, collection, Id::U8(1)), Err(PSP34Error::Custom("NotAnOwnerError".to_string())));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(1)), Some(storage_account));
+
+    set_caller(accounts.django);
+    assert_eq!(storage.transfer(accounts.bob, collection, Id::U8(1)), Ok(()));
+    assert_eq!(mock_psp34::owner_of(collection, Id::U8(1)), Some(accounts.bob));
+    match recorded_events().last() {
+        Some(Event::NftTransferred(event)) => {
+            assert_eq!((event.to, event.nft_account, event.nft_token.clone()), (accounts.bob, collection, Id::U8(1)));
+        },
+        _ => panic!("expected NftTransferred"),
+    }
+
+    // failure of the collection is passed to the caller
+    assert_eq!(storage.transfer(accounts.eve, collection, Id::U8(1)), Err(PSP34Error::NotApproved));
+    assert_eq!(storage.transfer(accounts.eve, collection, Id::U8(2)), Err(PSP34Error::TokenNotExists));
+    assert_eq!(recorded_events().len(), 1);
+}
@@ -0,0 +1,29 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+/// PSP34 collection in which anybody can mint, used by the end-to-end tests as the auctioned nfts
+#[openbrush::contract]
+pub mod psp34_collection {
+    use ink_storage::traits::SpreadAllocate;
+    use openbrush::contracts::psp34::extensions::mintable::*;
+    use openbrush::traits::Storage;
+
+    #[ink(storage)]
+    #[derive(Default, SpreadAllocate, Storage)]
+    pub struct Psp34Collection {
+        #[storage_field]
+        psp34: psp34::Data,
+    }
+
+    impl PSP34 for Psp34Collection {}
+
+    impl PSP34Mintable for Psp34Collection {}
+
+    impl Psp34Collection {
+        /// Constructor of an empty collection
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            ink_lang::codegen::initialize_contract(|_instance: &mut Self| {})
+        }
+    }
+}
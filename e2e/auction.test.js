@@ -0,0 +1,114 @@
+const { test, before, after } = require('node:test');
+const assert = require('node:assert');
+
+const chain = require('./chain');
+
+const createAuctionFee = 1000;
+const settlementFeeBps = 500;
+const cancellationFeeBps = 500;
+// the dev node seals a block for every transaction, so the auction is over once this much time passed
+const duration = 5000;
+
+let context;
+let alice, bob, charlie;
+let nftStorage, collection, alleblock;
+
+before(async () => {
+    context = await chain.startNode();
+    ({ alice, bob, charlie } = chain.accounts());
+
+    nftStorage = await chain.deploy(context.api, alice, 'nft_storage');
+    collection = await chain.deploy(context.api, alice, 'psp34_collection');
+    alleblock = await chain.deploy(context.api, alice, 'alleblock',
+        createAuctionFee, settlementFeeBps, cancellationFeeBps, alice.address, nftStorage.address);
+
+    // alleblock releases tokens through the storage, so it has to own it
+    await chain.call(nftStorage, alice, 'setOwner', 0, alleblock.address);
+});
+
+after(async () => {
+    await chain.stopNode(context);
+});
+
+// mint the token to the seller and create its auction, returns the auction id
+async function createNftAuction(seller, token) {
+    await chain.call(collection, seller, 'psp34Mintable::mint', 0, seller.address, token);
+    const args = [100, 'nft', duration, collection.address, token, null, null, null, null, null];
+
+    // alleblock has to be allowed to take the token first
+    const denied = await chain.query(alleblock, seller, 'createAuction', createAuctionFee, ...args);
+    assert.deepStrictEqual(denied, { err: 'NoNftAllowanceError' });
+    await chain.call(collection, seller, 'psp34::approve', 0, alleblock.address, token, true);
+
+    const created = await chain.query(alleblock, seller, 'createAuction', createAuctionFee, ...args);
+    await chain.call(alleblock, seller, 'createAuction', createAuctionFee, ...args);
+    assert.strictEqual(await ownerOf(token), nftStorage.address.toString());
+    return created.ok;
+};
+
+async function ownerOf(token) {
+    return chain.query(collection, alice, 'psp34::ownerOf', 0, token);
+};
+
+async function pendingWithdrawal(account) {
+    return chain.query(alleblock, alice, 'getPendingWithdrawal', 0, account.address);
+};
+
+test('nft storage is owned by alleblock', async () => {
+    assert.strictEqual(await chain.query(nftStorage, alice, 'getOwner', 0), alleblock.address.toString());
+});
+
+test('nft storage is still upgraded by the account which deployed it', async () => {
+    assert.strictEqual(await chain.query(nftStorage, alice, 'getAdmin', 0), alice.address);
+    assert.deepStrictEqual(await chain.query(nftStorage, alice, 'migrateStorage', 0), { ok: 1 });
+    assert.deepStrictEqual(await chain.query(nftStorage, bob, 'migrateStorage', 0), { err: 'NotAnAdminError' });
+});
+
+test('winner of an nft auction receives the token and the seller the price', async () => {
+    const token = { u8: 1 };
+    const auctionId = await createNftAuction(bob, token);
+
+    // bid leaves the buyer's account, the buyer pays for the transaction and storage deposits
+    const bid = 1_000_000;
+    const charlieBalance = await chain.freeBalance(context.api, charlie.address);
+    const bidResult = await chain.call(alleblock, charlie, 'bid', bid, auctionId);
+    assert.ok(chain.transfers(context.api, bidResult, charlie.address, alleblock.address).includes(BigInt(bid)));
+    assert.strictEqual(
+        await chain.freeBalance(context.api, charlie.address) - charlieBalance,
+        chain.netTransfer(context.api, bidResult, charlie.address) - chain.paidFee(context.api, bidResult)
+    );
+
+    await chain.sleep(duration + 1000);
+    await chain.call(alleblock, alice, 'finishAuction', 0, auctionId);
+    assert.strictEqual(await ownerOf(token), charlie.address);
+
+    const fee = bid * settlementFeeBps / 10_000;
+    assert.strictEqual(await pendingWithdrawal(bob), bid - fee);
+    // creation fee of the auction is waiting for the contract owner too
+    assert.strictEqual(await pendingWithdrawal(alice), createAuctionFee + fee);
+
+    // withdrawn price leaves the contract and reaches the seller's account, the seller pays for the transaction
+    // storage deposits are moved between the caller and the contract, so balances are compared with all the transfers
+    const contractBalance = await chain.freeBalance(context.api, alleblock.address);
+    const bobBalance = await chain.freeBalance(context.api, bob.address);
+    const withdrawResult = await chain.call(alleblock, bob, 'withdraw', 0);
+    assert.ok(chain.transfers(context.api, withdrawResult, alleblock.address, bob.address).includes(BigInt(bid - fee)));
+    assert.strictEqual(
+        await chain.freeBalance(context.api, alleblock.address) - contractBalance,
+        chain.netTransfer(context.api, withdrawResult, alleblock.address)
+    );
+    assert.strictEqual(
+        await chain.freeBalance(context.api, bob.address) - bobBalance,
+        chain.netTransfer(context.api, withdrawResult, bob.address) - chain.paidFee(context.api, withdrawResult)
+    );
+    assert.strictEqual(await pendingWithdrawal(bob), 0);
+});
+
+test('cancelled nft auction gives the token back to the seller', async () => {
+    const token = { u8: 2 };
+    const auctionId = await createNftAuction(bob, token);
+
+    const quote = await chain.query(alleblock, bob, 'quoteFees', 0, auctionId);
+    await chain.call(alleblock, bob, 'cancelAuction', quote.ok.cancellationCost, auctionId);
+    assert.strictEqual(await ownerOf(token), bob.address);
+});
@@ -0,0 +1,157 @@
+const { spawn, spawnSync } = require('child_process');
+const fs = require('fs');
+const path = require('path');
+
+const { ApiPromise, WsProvider } = require('@polkadot/api');
+const { CodePromise, ContractPromise } = require('@polkadot/api-contract');
+const { Keyring } = require('@polkadot/keyring');
+
+const nodeBinary = process.env.CONTRACTS_NODE || 'substrate-contracts-node';
+// the suite is written for this node release, its flags and weights change between releases
+const nodeVersion = require('./package.json').config.substrateContractsNode;
+const wsPort = process.env.CONTRACTS_NODE_WS_PORT || '9944';
+const contractsDir = path.join(__dirname, '..', 'contracts');
+
+// fail early on a node of another release, e.g. newer ones reject --ws-port
+function checkNodeVersion() {
+    const { stdout, error } = spawnSync(nodeBinary, ['--version'], { encoding: 'utf8' });
+    if (error) {
+        throw error;
+    }
+    if (!stdout.startsWith(`substrate-contracts-node ${nodeVersion}`)) {
+        throw new Error(`e2e runs on substrate-contracts-node ${nodeVersion}, found: ${stdout.trim()}`);
+    }
+};
+
+// start a fresh development chain, it's thrown away when the node stops
+async function startNode() {
+    checkNodeVersion();
+    const node = spawn(nodeBinary, ['--dev', '--tmp', '--ws-port', wsPort], { stdio: 'ignore' });
+    const failed = new Promise((_, reject) => {
+        node.on('error', reject);
+        node.on('exit', code => reject(new Error(`${nodeBinary} exited with code ${code}`)));
+    });
+
+    const provider = new WsProvider(`ws://127.0.0.1:${wsPort}`);
+    try {
+        const api = await Promise.race([ApiPromise.create({ provider }), failed]);
+        return { node, api };
+    }
+    catch (error) {
+        await provider.disconnect();
+        node.kill();
+        throw error;
+    }
+};
+
+async function stopNode({ node, api }) {
+    await api.disconnect();
+    node.kill();
+};
+
+// development accounts, funded in the genesis block
+function accounts() {
+    const keyring = new Keyring({ type: 'sr25519' });
+    return {
+        alice: keyring.addFromUri('//Alice'),
+        bob: keyring.addFromUri('//Bob'),
+        charlie: keyring.addFromUri('//Charlie'),
+    };
+};
+
+// gas for every call, pallet-contracts measures weight in time and proof size
+function gasLimit(api) {
+    return api.registry.createType('WeightV2', { refTime: 100_000_000_000, proofSize: 1_000_000 });
+};
+
+// bundle of metadata and wasm built by `cargo contract build` in the contract's directory
+function loadBundle(name) {
+    const file = path.join(contractsDir, name, 'target', 'ink', `${name}.contract`);
+    return JSON.parse(fs.readFileSync(file));
+};
+
+// send the transaction and wait until it's in a block, fail if the extrinsic or the contract failed
+function send(api, tx, signer) {
+    return new Promise((resolve, reject) => {
+        tx.signAndSend(signer, result => {
+            if (result.dispatchError) {
+                let message = result.dispatchError.toString();
+                if (result.dispatchError.isModule) {
+                    const { section, name } = api.registry.findMetaError(result.dispatchError.asModule);
+                    message = `${section}.${name}`;
+                }
+                reject(new Error(message));
+            }
+            else if (result.status.isInBlock) {
+                resolve(result);
+            }
+        }).catch(reject);
+    });
+};
+
+// upload the contract's code and instantiate it with the `new` constructor
+async function deploy(api, signer, name, ...args) {
+    const bundle = loadBundle(name);
+    const code = new CodePromise(api, bundle, bundle.source.wasm);
+    const tx = code.tx.new({ gasLimit: gasLimit(api), storageDepositLimit: null }, ...args);
+    const result = await send(api, tx, signer);
+    return new ContractPromise(api, bundle, result.contract.address);
+};
+
+// call a message in a transaction, trait messages are named like `psp34::approve`
+async function call(contract, signer, message, value, ...args) {
+    const tx = contract.tx[message]({ value, gasLimit: gasLimit(contract.api), storageDepositLimit: null }, ...args);
+    return send(contract.api, tx, signer);
+};
+
+// dry-run a message and return what it would return, as json
+async function query(contract, caller, message, value, ...args) {
+    const { result, output } = await contract.query[message](caller.address, { value, gasLimit: gasLimit(contract.api), storageDepositLimit: null }, ...args);
+    if (result.isErr) {
+        throw new Error(result.asErr.toString());
+    }
+    return output.toJSON();
+};
+
+async function freeBalance(api, address) {
+    const { data } = await api.query.system.account(address);
+    return data.free.toBigInt();
+};
+
+// fee and tip the signer paid for the transaction
+function paidFee(api, result) {
+    const { event } = result.events.find(({ event }) => api.events.transactionPayment.TransactionFeePaid.is(event));
+    const [, actualFee, tip] = event.data;
+    return actualFee.toBigInt() + tip.toBigInt();
+};
+
+// amounts moved by the transaction from one account to another, storage deposits are transfers too
+function transfers(api, result, from, to) {
+    return result.events
+        .filter(({ event }) => api.events.balances.Transfer.is(event))
+        .map(({ event }) => event.data)
+        .filter(([source, destination]) => source.toString() === from.toString() && destination.toString() === to.toString())
+        .map(([, , amount]) => amount.toBigInt());
+};
+
+// amount the account received from the transaction's transfers minus the amount it sent
+function netTransfer(api, result, account) {
+    return result.events
+        .filter(({ event }) => api.events.balances.Transfer.is(event))
+        .map(({ event }) => event.data)
+        .reduce((sum, [source, destination, amount]) => {
+            if (destination.toString() === account.toString()) {
+                sum += amount.toBigInt();
+            }
+            if (source.toString() === account.toString()) {
+                sum -= amount.toBigInt();
+            }
+            return sum;
+        }, 0n);
+};
+
+function sleep(ms) {
+    return new Promise(resolve => setTimeout(resolve, ms));
+};
+
+module.exports = { startNode, stopNode, accounts, deploy, call, query, freeBalance, paidFee, transfers, netTransfer, sleep };